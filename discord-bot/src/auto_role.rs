@@ -0,0 +1,269 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use log::{info, warn};
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, CreateAllowedMentions, CreateMessage, GuildId, Permissions, RoleId,
+};
+use welcome_service::{guild, join_role};
+
+use crate::{Data, PoiseError};
+
+/// The Discord error code returned when a member is no longer in the guild.
+const UNKNOWN_MEMBER: isize = 10007;
+
+/// Assigns the join roles of a guild to a new member.
+///
+/// Bots only get the roles marked for bots, and other members only get the roles which
+/// are not. The roles are assigned in the background, so the welcome is not held up by
+/// the role requests, and roles with a delay once it has passed. Pending delays are lost
+/// when the bot restarts.
+///
+/// Members who still have to pass the membership screening of the guild get their roles
+/// once they accepted the rules, see [`assign_join_roles_after_screening`].
+///
+/// Roles the bot cannot assign are reported to the moderation channel instead of
+/// failing the join handling.
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `data` - The shared bot data.
+/// * `guild` - The guild model.
+/// * `guild_id` - The guild the member joined.
+/// * `user` - The user who joined.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if loading the join roles fails.
+#[fastrace::trace]
+pub async fn assign_join_roles(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild: &entity::guild::Model,
+    guild_id: GuildId,
+    user: &serenity::User,
+) -> Result<(), PoiseError> {
+    let mut roles_by_delay: BTreeMap<u64, Vec<RoleId>> = BTreeMap::new();
+
+    for join_role in join_role::get_all(&data.conn, guild.id).await? {
+        if join_role.for_bots == user.bot {
+            roles_by_delay
+                .entry(join_role.delay_seconds.max(0) as u64)
+                .or_default()
+                .push(RoleId::new(join_role.role_id as u64));
+        }
+    }
+
+    if roles_by_delay.is_empty() {
+        return Ok(());
+    }
+
+    let ctx = ctx.clone();
+    let guild = guild.clone();
+    let user = user.clone();
+
+    tokio::spawn(async move {
+        let mut waited = 0;
+
+        for (delay, role_ids) in roles_by_delay {
+            if delay > waited {
+                tokio::time::sleep(Duration::from_secs(delay - waited)).await;
+                waited = delay;
+            }
+
+            add_roles(&ctx, &guild, guild_id, &user, &role_ids).await;
+        }
+    });
+
+    Ok(())
+}
+
+/// Assigns the join roles once a member passed the membership screening of the guild.
+///
+/// The screening is passed when a member update clears the pending flag of a member
+/// who was pending before. If the previous member data is not cached, e.g. because the
+/// bot restarted in between, the roles are not assigned. Failures are logged.
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `data` - The shared bot data.
+/// * `old` - The member data before the update, if it was cached.
+/// * `event` - The guild member update event.
+#[fastrace::trace]
+pub async fn assign_join_roles_after_screening(
+    ctx: &serenity::Context,
+    data: &Data,
+    old: Option<&serenity::Member>,
+    event: &serenity::GuildMemberUpdateEvent,
+) {
+    if event.pending || !old.is_some_and(|old| old.pending) {
+        return;
+    }
+
+    info!("'{}' passed the membership screening.", event.user.name);
+
+    let result = match guild::get_by_guild_id(&data.conn, event.guild_id.into()).await {
+        Ok(Some(guild)) => assign_join_roles(ctx, data, &guild, event.guild_id, &event.user).await,
+        Ok(None) => Ok(()),
+        Err(why) => Err(why.into()),
+    };
+
+    if let Err(why) = result {
+        warn!(
+            "Could not assign the join roles of '{}': {why}",
+            event.user.name
+        );
+    }
+}
+
+/// Adds roles to a member, reporting every role which cannot be assigned.
+///
+/// Every role is added with its own request, which leaves roles the member got in the
+/// meantime untouched.
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `guild` - The guild model.
+/// * `guild_id` - The guild of the member.
+/// * `user` - The member to add the roles to.
+/// * `role_ids` - The roles to add.
+#[fastrace::trace]
+async fn add_roles(
+    ctx: &serenity::Context,
+    guild: &entity::guild::Model,
+    guild_id: GuildId,
+    user: &serenity::User,
+    role_ids: &[RoleId],
+) {
+    for role_id in role_ids {
+        if let Err(why) = check_assignable(&ctx.cache, guild_id, *role_id) {
+            report_failure(ctx, guild, user, *role_id, &why).await;
+            continue;
+        }
+
+        match ctx
+            .http
+            .add_member_role(guild_id, user.id, *role_id, Some("Join role"))
+            .await
+        {
+            Ok(()) => info!("Assigned join role '{role_id}' to '{}'.", user.name),
+            Err(serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response)))
+                if response.error.code == UNKNOWN_MEMBER =>
+            {
+                info!(
+                    "'{}' left before the join role '{role_id}' was assigned.",
+                    user.name
+                );
+            }
+            Err(why) => report_failure(ctx, guild, user, *role_id, &why.to_string()).await,
+        }
+    }
+}
+
+/// Checks whether the bot is able to assign a role.
+///
+/// A role can be assigned if it exists, is not the @everyone role, is not managed by an
+/// integration, the bot has the `MANAGE_ROLES` permission and the role is below the
+/// highest role of the bot. If the guild is not cached, the check passes and Discord
+/// decides.
+///
+/// # Arguments
+/// * `cache` - The Serenity cache.
+/// * `guild_id` - The guild the role belongs to.
+/// * `role_id` - The role to check.
+///
+/// # Returns
+/// Returns a message describing why the role cannot be assigned.
+pub fn check_assignable(
+    cache: &serenity::Cache,
+    guild_id: GuildId,
+    role_id: RoleId,
+) -> Result<(), String> {
+    let bot_id = cache.current_user().id;
+
+    let Some(guild) = cache.guild(guild_id) else {
+        return Ok(());
+    };
+
+    let everyone_id = RoleId::new(guild_id.get());
+
+    if role_id == everyone_id {
+        return Err("Every member already has the @everyone role.".to_string());
+    }
+
+    let Some(role) = guild.roles.get(&role_id) else {
+        return Err("The role no longer exists.".to_string());
+    };
+
+    if role.managed {
+        return Err("The role is managed by an integration.".to_string());
+    }
+
+    let Some(bot_member) = guild.members.get(&bot_id) else {
+        return Ok(());
+    };
+
+    // Role permissions apply guild-wide, channel overwrites do not matter here.
+    let permissions = bot_member
+        .roles
+        .iter()
+        .chain([&everyone_id])
+        .filter_map(|role_id| guild.roles.get(role_id))
+        .fold(Permissions::empty(), |permissions, role| {
+            permissions | role.permissions
+        });
+
+    if guild.owner_id != bot_id && !permissions.administrator() && !permissions.manage_roles() {
+        return Err("The bot is missing the Manage Roles permission.".to_string());
+    }
+
+    let bot_position = guild
+        .member_highest_role(bot_member)
+        .map_or(0, |bot_role| bot_role.position);
+
+    if role.position >= bot_position {
+        return Err(
+            "The role is not below the highest role of the bot in the role list.".to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Reports a join role which could not be assigned to the moderation channel.
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `guild` - The guild model.
+/// * `user` - The member the role was meant for.
+/// * `role_id` - The role which could not be assigned.
+/// * `reason` - The reason the role could not be assigned.
+#[fastrace::trace]
+async fn report_failure(
+    ctx: &serenity::Context,
+    guild: &entity::guild::Model,
+    user: &serenity::User,
+    role_id: RoleId,
+    reason: &str,
+) {
+    warn!(
+        "Could not assign join role '{role_id}' to '{}': {reason}",
+        user.name
+    );
+
+    let Some(moderation_channel_id) = guild.moderation_channel_id else {
+        return;
+    };
+
+    let message = CreateMessage::new()
+        .content(format!(
+            "Could not assign the join role <@&{role_id}> to <@{}>: {reason}",
+            user.id
+        ))
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    if let Err(why) = ChannelId::new(moderation_channel_id as u64)
+        .send_message(&ctx.http, message)
+        .await
+    {
+        warn!("Could not report the join role failure: {why}");
+    }
+}
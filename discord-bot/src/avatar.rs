@@ -0,0 +1,308 @@
+use std::time::Duration;
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont, point};
+use image::{DynamicImage, Rgba, RgbaImage, imageops::FilterType};
+use img_gen::{animation::FrameSequence, cache::LruCache, source::ImageSource};
+use log::warn;
+use poise::serenity_prelude::{self as serenity, UserId};
+use thiserror::Error;
+
+/// The width and height of the avatars requested from the CDN, in pixels.
+const AVATAR_SIZE: u32 = 256;
+/// The maximum size of a downloaded avatar in bytes.
+const MAX_AVATAR_BYTES: u64 = 8 * 1024 * 1024;
+/// The time after which an avatar download is abandoned.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(5);
+/// The maximum size of the decoded avatars kept in memory, in bytes.
+const CACHE_CAPACITY_BYTES: usize = 256 * 1024 * 1024;
+/// The maximum number of frames kept of animated avatars, the frame limit of animated
+/// welcome images.
+const MAX_AVATAR_FRAMES: usize = 50;
+/// The background colors of initials avatars, picked by user ID.
+const INITIALS_COLORS: [[u8; 4]; 6] = [
+    [88, 101, 242, 255],
+    [87, 242, 135, 255],
+    [254, 231, 92, 255],
+    [235, 69, 158, 255],
+    [237, 66, 69, 255],
+    [116, 127, 141, 255],
+];
+
+/// Errors that can occur while fetching an avatar.
+#[derive(Error, Debug)]
+pub enum AvatarError {
+    /// The avatar could not be downloaded.
+    #[error("The avatar could not be downloaded: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// The avatar is larger than [`MAX_AVATAR_BYTES`].
+    #[error("The avatar is larger than {MAX_AVATAR_BYTES} bytes.")]
+    TooLarge,
+
+    /// The avatar could not be decoded or resized.
+    #[error("The avatar could not be decoded: {0}")]
+    Image(#[from] img_gen::error::Error),
+
+    /// The blocking task decoding the avatar panicked.
+    #[error("The avatar could not be processed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+/// Fetches the avatars shown on welcome and farewell cards.
+///
+/// Avatars are requested from the CDN in a fixed size and format, downloaded with a
+/// timeout and size limit and decoded on a blocking thread, so the async runtime is not
+/// held up. The decoded frames are handed to the image generator as they are, without
+/// encoding them again. Processed avatars are cached by user ID and avatar hash, so a
+/// changed avatar is fetched again, and the cache is limited by the size of the decoded
+/// frames. If an avatar cannot be fetched, an avatar showing the initials of the member
+/// is rendered instead, so a CDN hiccup never costs the card.
+pub struct AvatarService {
+    /// The HTTP client used for downloads.
+    client: reqwest::Client,
+    /// The font used to draw initials avatars.
+    font: FontVec,
+    /// The avatars by user ID and avatar hash.
+    cache: LruCache<(UserId, String), ImageSource>,
+}
+
+impl AvatarService {
+    /// Creates a new `AvatarService`.
+    ///
+    /// # Arguments
+    /// * `font` - The font used to draw initials avatars.
+    ///
+    /// # Errors
+    /// Returns a [`reqwest::Error`] if the HTTP client cannot be created.
+    pub fn new(font: FontVec) -> Result<Self, reqwest::Error> {
+        let client = reqwest::Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .build()?;
+
+        Ok(Self {
+            client,
+            font,
+            cache: LruCache::with_weigher(CACHE_CAPACITY_BYTES, decoded_size),
+        })
+    }
+
+    /// Returns the avatar of a user, preferring the guild avatar of the member.
+    ///
+    /// # Arguments
+    /// * `user` - The user whose avatar is shown.
+    /// * `member` - The member data of the user, if available.
+    ///
+    /// # Returns
+    /// Returns the avatar, or an initials avatar if it cannot be fetched.
+    #[fastrace::trace]
+    pub async fn get(
+        &self,
+        user: &serenity::User,
+        member: Option<&serenity::Member>,
+    ) -> ImageSource {
+        let (hash, url) = avatar_url(user, member);
+        let key = (user.id, hash);
+
+        if let Some(avatar) = self.cache.get(&key) {
+            return avatar;
+        }
+
+        match self.fetch(&url).await {
+            Ok(avatar) => {
+                // Older avatars of the user are outdated once the hash changed.
+                self.cache.retain(|(user_id, _), _| *user_id != key.0);
+                self.cache.insert(key, avatar.clone());
+                avatar
+            }
+            Err(why) => {
+                warn!(
+                    "Could not fetch the avatar of '{}', using initials: {why}",
+                    user.name
+                );
+
+                let name =
+                    member.map_or_else(|| user.display_name(), serenity::Member::display_name);
+                ImageSource::from(self.initials_avatar(user.id, name))
+            }
+        }
+    }
+
+    /// Downloads and processes an avatar.
+    ///
+    /// # Arguments
+    /// * `url` - The CDN URL of the avatar.
+    ///
+    /// # Errors
+    /// Returns an [`AvatarError`] if the download fails, exceeds the size limit or
+    /// cannot be decoded.
+    #[fastrace::trace]
+    async fn fetch(&self, url: &str) -> Result<ImageSource, AvatarError> {
+        let mut response = self.client.get(url).send().await?.error_for_status()?;
+
+        if response
+            .content_length()
+            .is_some_and(|length| length > MAX_AVATAR_BYTES)
+        {
+            return Err(AvatarError::TooLarge);
+        }
+
+        let mut image_bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if (image_bytes.len() + chunk.len()) as u64 > MAX_AVATAR_BYTES {
+                return Err(AvatarError::TooLarge);
+            }
+
+            image_bytes.extend_from_slice(&chunk);
+        }
+
+        let frames = tokio::task::spawn_blocking(move || decode_avatar(&image_bytes)).await??;
+
+        Ok(ImageSource::from(frames))
+    }
+
+    /// Renders an avatar showing the initials of a name on a colored background.
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the user, which picks the background color.
+    /// * `name` - The name to take the initials from.
+    ///
+    /// # Returns
+    /// Returns the rendered avatar.
+    #[fastrace::trace]
+    fn initials_avatar(&self, user_id: UserId, name: &str) -> DynamicImage {
+        let color = INITIALS_COLORS[(user_id.get() % INITIALS_COLORS.len() as u64) as usize];
+        let mut image = RgbaImage::from_pixel(AVATAR_SIZE, AVATAR_SIZE, Rgba(color));
+
+        let scale = PxScale::from(AVATAR_SIZE as f32 * 0.45);
+        let font = self.font.as_scaled(scale);
+
+        let initials = initials(name)
+            .chars()
+            .filter(|c| self.font.glyph_id(*c).0 != 0)
+            .collect::<Vec<_>>();
+        let width: f32 = initials
+            .iter()
+            .map(|c| font.h_advance(font.glyph_id(*c)))
+            .sum();
+
+        let mut x = (AVATAR_SIZE as f32 - width) / 2.;
+        let baseline = (AVATAR_SIZE as f32 + font.ascent() + font.descent()) / 2.;
+
+        for c in initials {
+            let glyph = font
+                .glyph_id(c)
+                .with_scale_and_position(scale, point(x, baseline));
+            x += font.h_advance(glyph.id);
+
+            let Some(outlined) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+
+            let bounds = outlined.px_bounds();
+            outlined.draw(|glyph_x, glyph_y, coverage| {
+                let (x, y) = (
+                    bounds.min.x as i32 + glyph_x as i32,
+                    bounds.min.y as i32 + glyph_y as i32,
+                );
+
+                if x >= 0 && y >= 0 && x < AVATAR_SIZE as i32 && y < AVATAR_SIZE as i32 {
+                    let pixel = image.get_pixel_mut(x as u32, y as u32);
+
+                    for channel in 0..3 {
+                        let background = f32::from(pixel[channel]);
+                        pixel[channel] = (background + (255. - background) * coverage) as u8;
+                    }
+                }
+            });
+        }
+
+        DynamicImage::ImageRgba8(image)
+    }
+}
+
+/// Returns the avatar hash and CDN URL of the avatar shown for a user.
+///
+/// The guild avatar of the member is preferred over the user avatar. Animated avatars
+/// are requested as GIF and still avatars as PNG, both in [`AVATAR_SIZE`]. Users without
+/// an avatar get their default avatar, with the hash `default`.
+///
+/// # Arguments
+/// * `user` - The user whose avatar is shown.
+/// * `member` - The member data of the user, if available.
+fn avatar_url(user: &serenity::User, member: Option<&serenity::Member>) -> (String, String) {
+    let extension = |hash: &serenity::ImageHash| if hash.is_animated() { "gif" } else { "png" };
+
+    if let Some((member, hash)) = member.and_then(|member| Some((member, member.avatar?))) {
+        let url = format!(
+            "https://cdn.discordapp.com/guilds/{}/users/{}/avatars/{hash}.{}?size={AVATAR_SIZE}",
+            member.guild_id,
+            user.id,
+            extension(&hash)
+        );
+        return (hash.to_string(), url);
+    }
+
+    if let Some(hash) = user.avatar {
+        let url = format!(
+            "https://cdn.discordapp.com/avatars/{}/{hash}.{}?size={AVATAR_SIZE}",
+            user.id,
+            extension(&hash)
+        );
+        return (hash.to_string(), url);
+    }
+
+    ("default".to_string(), user.default_avatar_url())
+}
+
+/// Returns the initials of a name, the first letter of up to two words.
+///
+/// Names without letters or digits, e.g. only emoji, use a question mark.
+fn initials(name: &str) -> String {
+    let initials = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect::<String>();
+
+    if initials.is_empty() {
+        "?".to_string()
+    } else {
+        initials
+    }
+}
+
+/// Decodes an avatar and resizes every frame to [`AVATAR_SIZE`].
+///
+/// Still avatars are resized with a smooth filter. Animated avatars are sampled down to
+/// [`MAX_AVATAR_FRAMES`] and use a fast filter, since every frame has to be resized.
+///
+/// # Arguments
+/// * `image_bytes` - The downloaded avatar.
+///
+/// # Returns
+/// Returns the decoded and resized frames.
+///
+/// # Errors
+/// Returns an [`img_gen::error::Error`] if decoding the avatar fails.
+#[fastrace::trace]
+fn decode_avatar(image_bytes: &[u8]) -> Result<FrameSequence, img_gen::error::Error> {
+    let frames = FrameSequence::decode(image_bytes)?.sample(MAX_AVATAR_FRAMES);
+    let filter = if frames.is_animated() {
+        FilterType::Nearest
+    } else {
+        FilterType::Triangle
+    };
+
+    frames.try_map(|frame| Ok(frame.resize(AVATAR_SIZE, AVATAR_SIZE, filter)))
+}
+
+/// Returns the size of a cached avatar in bytes.
+fn decoded_size(avatar: &ImageSource) -> usize {
+    match avatar {
+        ImageSource::Path(_) => 0,
+        ImageSource::Bytes(bytes) => bytes.len(),
+        ImageSource::Image(image) => image.as_bytes().len(),
+        ImageSource::Frames(frames) => frames.byte_size(),
+    }
+}
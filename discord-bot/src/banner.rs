@@ -0,0 +1,150 @@
+use std::{io::Cursor, path::PathBuf};
+
+use chrono::Utc;
+use image::{ImageFormat, ImageReader};
+use log::info;
+use migration::sea_orm::DbConn;
+use poise::serenity_prelude::{self as serenity};
+
+use crate::PoiseError;
+
+/// The ID of the seeded image drawn behind the avatar, unless configured.
+pub const DEFAULT_BACK_BANNER: i32 = 1;
+/// The ID of the seeded image drawn over the avatar, unless configured.
+pub const DEFAULT_FRONT_BANNER: i32 = 2;
+/// The directory uploaded banners are stored in, unless `WELCOMEBOT_UPLOADS_DIR` is set.
+const DEFAULT_UPLOADS_DIR: &str = "assets/uploads";
+/// The maximum size of an uploaded banner in bytes.
+const MAX_BANNER_SIZE: u32 = 8 * 1024 * 1024;
+/// The minimum width and height of a banner in pixels.
+const MIN_BANNER_DIMENSION: u32 = 64;
+/// The maximum width and height of a banner in pixels.
+const MAX_BANNER_DIMENSION: u32 = 4096;
+/// The maximum width and height of an animated banner in pixels.
+const MAX_ANIMATED_BANNER_DIMENSION: u32 = 1024;
+/// The maximum number of pixels of all frames of an animated banner together, which
+/// keeps the decoded frames below 64 MB.
+const MAX_ANIMATED_BANNER_PIXELS: u64 = 16_000_000;
+/// The image formats accepted for banners.
+const BANNER_FORMATS: [ImageFormat; 4] = [
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::WebP,
+    ImageFormat::Gif,
+];
+
+/// Validates an uploaded banner and stores it as a new image.
+///
+/// The dimensions are read from the image header and checked before the image is
+/// decoded, so an upload declaring huge dimensions is rejected without allocating them.
+/// Renders decode and cache every frame of an animated banner, so the frames are
+/// counted from the container as well and limited in size and number.
+/// Decoding and writing the file run on a blocking thread. The file is written to the
+/// uploads directory, `WELCOMEBOT_UPLOADS_DIR` or `assets/uploads` by default, under a
+/// generated name.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `file` - The uploaded banner.
+/// * `create_user_id` - The ID of the user uploading the banner.
+///
+/// # Returns
+/// Returns the new image if the banner is valid, or a message describing the problem.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if downloading, writing the file or the database operation fails.
+#[fastrace::trace]
+pub async fn store_banner(
+    db: &DbConn,
+    file: &serenity::Attachment,
+    create_user_id: i64,
+) -> Result<Result<entity::image::Model, String>, PoiseError> {
+    if file.size > MAX_BANNER_SIZE {
+        return Ok(Err(format!(
+            "The file is larger than {} MiB.",
+            MAX_BANNER_SIZE / 1024 / 1024
+        )));
+    }
+
+    let content = file.download().await?;
+
+    let format = match image::guess_format(&content) {
+        Ok(format) if BANNER_FORMATS.contains(&format) => format,
+        _ => {
+            return Ok(Err(
+                "The file must be a PNG, JPEG, WebP or GIF image.".to_string()
+            ));
+        }
+    };
+
+    let Ok((width, height)) =
+        ImageReader::with_format(Cursor::new(&content), format).into_dimensions()
+    else {
+        return Ok(Err("The image could not be decoded.".to_string()));
+    };
+
+    let dimensions = MIN_BANNER_DIMENSION..=MAX_BANNER_DIMENSION;
+    if !dimensions.contains(&width) || !dimensions.contains(&height) {
+        return Ok(Err(format!(
+            "The image is {width}x{height} pixels, but width and height must be between {MIN_BANNER_DIMENSION} and {MAX_BANNER_DIMENSION} pixels."
+        )));
+    }
+
+    let frames = img_gen::animation::frame_count(&content);
+    if frames > 1 {
+        if width > MAX_ANIMATED_BANNER_DIMENSION || height > MAX_ANIMATED_BANNER_DIMENSION {
+            return Ok(Err(format!(
+                "The animation is {width}x{height} pixels, but animated images must be at most {MAX_ANIMATED_BANNER_DIMENSION}x{MAX_ANIMATED_BANNER_DIMENSION} pixels."
+            )));
+        }
+
+        if u64::from(width) * u64::from(height) * frames as u64 > MAX_ANIMATED_BANNER_PIXELS {
+            return Ok(Err(format!(
+                "The animation has {frames} frames of {width}x{height} pixels, which is more than {} megapixels in total. Please use fewer or smaller frames.",
+                MAX_ANIMATED_BANNER_PIXELS / 1_000_000
+            )));
+        }
+    }
+
+    let uploads_dir = PathBuf::from(
+        std::env::var("WELCOMEBOT_UPLOADS_DIR").unwrap_or_else(|_| DEFAULT_UPLOADS_DIR.to_string()),
+    );
+    let server_name = format!("{}.{}", uuid::Uuid::new_v4(), format.extensions_str()[0]);
+    let path = uploads_dir.join(&server_name);
+    let size = content.len() as i64;
+
+    let stored = {
+        let path = path.clone();
+
+        tokio::task::spawn_blocking(move || -> std::io::Result<bool> {
+            // Decode the whole image, so broken files are rejected before they are stored.
+            if image::load_from_memory_with_format(&content, format).is_err() {
+                return Ok(false);
+            }
+
+            std::fs::create_dir_all(&uploads_dir)?;
+            std::fs::write(&path, &content)?;
+            Ok(true)
+        })
+        .await??
+    };
+
+    if !stored {
+        return Ok(Err("The image could not be decoded.".to_string()));
+    }
+    info!("Stored banner '{}' as '{}'.", file.filename, path.display());
+
+    let image = entity::image::Model {
+        id: 0,
+        original_name: file.filename.clone(),
+        server_name,
+        path: path.to_string_lossy().into_owned(),
+        width: width as i32,
+        height: height as i32,
+        size,
+        create_user_id,
+        create_date: Utc::now().naive_utc().to_string(),
+    };
+
+    Ok(Ok(welcome_service::image::create(db, image).await?))
+}
@@ -0,0 +1,212 @@
+use chrono::Utc;
+use poise::{
+    CreateReply,
+    serenity_prelude::{self as serenity, CreateAllowedMentions},
+};
+use welcome_service::{guild, join_role};
+
+use crate::{Context, PoiseError, auto_role::check_assignable};
+
+/// Commands for assigning roles to members when they join.
+///
+/// This command serves as the entry point for join role subcommands.
+/// It is a slash command that is only available in guilds and requires the
+/// user to have `ADMINISTRATOR` permissions.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if sending the response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR",
+    subcommands("add", "remove", "list")
+)]
+pub async fn autorole(ctx: Context<'_>) -> Result<(), PoiseError> {
+    ctx.say("How did you manage to do this?").await?;
+    Ok(())
+}
+
+/// Adds a role which is assigned to members when they join.
+///
+/// Adding a role again replaces its settings. Roles the bot cannot assign, because
+/// they are managed by an integration or not below the highest role of the bot, are
+/// rejected.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `role` - The role to assign.
+/// * `bots` - An optional flag to assign the role to bots instead of members.
+/// * `delay` - An optional number of seconds to wait before assigning the role.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn add(
+    ctx: Context<'_>,
+    #[description = "The role to assign on join"] role: serenity::Role,
+    #[description = "Assign the role to bots instead of members"] bots: Option<bool>,
+    #[description = "The number of seconds to wait before assigning the role"]
+    #[max = 86400]
+    delay: Option<u32>,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    // Unwrap since this is a guild-only command
+    let discord_guild = ctx.guild().unwrap().clone();
+    let author_id = ctx.author().id.into();
+
+    if let Err(why) = check_assignable(ctx.cache(), discord_guild.id, role.id) {
+        ctx.send(
+            CreateReply::default()
+                .content(format!("The role cannot be assigned by the bot: {why}"))
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    // Retrieve or create the guild entry in the database
+    let guild =
+        guild::get_or_create(db, discord_guild.id.into(), discord_guild.name, author_id).await?;
+
+    join_role::delete_by_role_id(db, guild.id, role.id.into()).await?;
+    join_role::create(
+        db,
+        entity::join_role::Model {
+            id: 0,
+            guild_id: guild.id,
+            role_id: role.id.into(),
+            for_bots: bots.unwrap_or(false),
+            delay_seconds: delay.unwrap_or(0) as i32,
+            create_user_id: author_id,
+            create_date: Utc::now(),
+        },
+    )
+    .await?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("The role <@&{}> is assigned on join.", role.id))
+            .allowed_mentions(CreateAllowedMentions::new())
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Removes a role from the roles assigned on join.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `role` - The role to no longer assign.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn remove(
+    ctx: Context<'_>,
+    #[description = "The role to no longer assign on join"] role: serenity::Role,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    // Unwrap since this is a guild-only command
+    let guild_id = ctx.guild_id().unwrap();
+
+    let removed = match guild::get_by_guild_id(db, guild_id.into()).await? {
+        Some(guild) => {
+            join_role::delete_by_role_id(db, guild.id, role.id.into())
+                .await?
+                .rows_affected
+                > 0
+        }
+        None => false,
+    };
+
+    let content = if removed {
+        format!("The role <@&{}> is no longer assigned on join.", role.id)
+    } else {
+        format!("The role <@&{}> is not assigned on join.", role.id)
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new())
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Lists the roles assigned on join.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn list(ctx: Context<'_>) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    // Unwrap since this is a guild-only command
+    let guild_id = ctx.guild_id().unwrap();
+
+    let join_roles = match guild::get_by_guild_id(db, guild_id.into()).await? {
+        Some(guild) => join_role::get_all(db, guild.id).await?,
+        None => Vec::new(),
+    };
+
+    let content = if join_roles.is_empty() {
+        "No roles are assigned on join.".to_string()
+    } else {
+        join_roles
+            .iter()
+            .map(|join_role| {
+                let target = if join_role.for_bots {
+                    "bots"
+                } else {
+                    "members"
+                };
+
+                match join_role.delay_seconds {
+                    0 => format!("- <@&{}> for {target}", join_role.role_id),
+                    delay => format!(
+                        "- <@&{}> for {target} after {delay} seconds",
+                        join_role.role_id
+                    ),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new())
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
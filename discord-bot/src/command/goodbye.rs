@@ -0,0 +1,318 @@
+use chrono::Utc;
+use migration::sea_orm::DbConn;
+use poise::{
+    CreateReply,
+    serenity_prelude::{self as serenity},
+};
+use welcome_service::{goodbye_settings, guild};
+
+use crate::{
+    Context, PoiseError,
+    banner::{DEFAULT_BACK_BANNER, DEFAULT_FRONT_BANNER},
+    command::welcome::{check_text, read_template},
+    text_template::VARIABLES,
+};
+
+/// Commands for saying goodbye to users with the welcome bot.
+///
+/// This command serves as the entry point for goodbye-related subcommands.
+/// It is a slash command that is only available in guilds and requires the
+/// user to have `ADMINISTRATOR` permissions.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if sending the response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR",
+    subcommands("settings", "template")
+)]
+pub async fn goodbye(ctx: Context<'_>) -> Result<(), PoiseError> {
+    ctx.say("How did you manage to do this?").await?;
+    Ok(())
+}
+
+/// Settings for the goodbye messages sent when members leave.
+///
+/// This command allows administrators to configure the goodbye message, the texts of
+/// the farewell card and the channel where goodbye messages are sent.
+///
+/// Goodbye texts describe the member who left with the same [`VARIABLES`] as welcome
+/// texts. A text which does not parse is refused and the reply points at the problem.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `chat_message` - An optional text for the chat goodbye message.
+/// * `image_headline` - An optional text for the card headline.
+/// * `image_subline` - An optional text for the card subline.
+/// * `channel` - An optional text channel where goodbye messages should be sent.
+/// * `enabled` - An optional flag to enable or disable goodbye messages.
+/// * `card` - An optional flag to enable or disable the farewell card.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn settings(
+    ctx: Context<'_>,
+    #[description = "The text of the chat goodbye message, e.g. Goodbye {name}, thanks for being here"]
+    chat_message: Option<String>,
+    #[description = "The text of the headline of the card, e.g. {name} left the server"]
+    image_headline: Option<String>,
+    #[description = "The text of the subline of the card, e.g. We are {members} members now"]
+    image_subline: Option<String>,
+    #[description = "The channel where to send goodbye messages to"]
+    #[channel_types("Text")]
+    channel: Option<serenity::Channel>,
+    #[description = "Enables or disables the goodbye message sending"] enabled: Option<bool>,
+    #[description = "Enables or disables the farewell card"] card: Option<bool>,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    let texts = [
+        ("chat message", &chat_message),
+        ("image headline", &image_headline),
+        ("image subline", &image_subline),
+    ];
+
+    for (field, text) in texts {
+        if let Some(Err(why)) = text.as_deref().map(|text| check_text(text, VARIABLES)) {
+            ctx.send(
+                CreateReply::default()
+                    .content(format!("The {field} is invalid: {why}"))
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    }
+
+    // Unwrap since this is a guild-only command
+    let discord_guild = ctx.guild().unwrap().clone();
+    let author_id = ctx.author().id.into();
+
+    // Retrieve or create the guild entry in the database
+    let guild =
+        guild::get_or_create(db, discord_guild.id.into(), discord_guild.name, author_id).await?;
+    let options = GoodbyeOptions {
+        chat_message,
+        image_headline,
+        image_subline,
+        enabled,
+        card,
+        channel: channel.map(|x| x.id()).or(discord_guild.system_channel_id),
+    };
+    update_goodbye_settings(db, guild, author_id, options).await?;
+
+    // Send a confirmation message
+    ctx.send(
+        CreateReply::default()
+            .content("Settings updated.")
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// The goodbye settings changed by the settings command, `None` keeps the current value.
+struct GoodbyeOptions {
+    /// An optional text for the chat goodbye message.
+    chat_message: Option<String>,
+    /// An optional text for the card headline.
+    image_headline: Option<String>,
+    /// An optional text for the card subline.
+    image_subline: Option<String>,
+    /// An optional flag to enable or disable goodbye messages.
+    enabled: Option<bool>,
+    /// An optional flag to enable or disable the farewell card.
+    card: Option<bool>,
+    /// An optional channel ID where goodbye messages should be sent.
+    channel: Option<serenity::ChannelId>,
+}
+
+/// Updates the goodbye settings for the guild.
+///
+/// This function updates the goodbye settings in the database for the specified guild.
+/// If no settings exist, it creates new ones.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `guild` - The guild model to update.
+/// * `create_user_id` - The ID of the user making the changes.
+/// * `options` - The changed goodbye settings.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation fails.
+#[fastrace::trace]
+async fn update_goodbye_settings(
+    db: &DbConn,
+    mut guild: entity::guild::Model,
+    create_user_id: i64,
+    options: GoodbyeOptions,
+) -> Result<entity::guild::Model, PoiseError> {
+    let GoodbyeOptions {
+        chat_message,
+        image_headline,
+        image_subline,
+        enabled,
+        card,
+        channel,
+    } = options;
+
+    let goodbye_settings = match guild.goodbye_settings_id {
+        Some(settings_id) => goodbye_settings::get_one(db, settings_id).await?,
+        None => None,
+    };
+
+    if let Some(mut goodbye_settings) = goodbye_settings {
+        // Update existing goodbye settings
+        goodbye_settings.goodbye_channel = match channel {
+            Some(c) => c.into(),
+            None => goodbye_settings.goodbye_channel,
+        };
+        goodbye_settings.chat_message = chat_message.unwrap_or(goodbye_settings.chat_message);
+        goodbye_settings.image_headline = image_headline.unwrap_or(goodbye_settings.image_headline);
+        goodbye_settings.image_subtext = image_subline.unwrap_or(goodbye_settings.image_subtext);
+        goodbye_settings.enabled = enabled.unwrap_or(goodbye_settings.enabled);
+        goodbye_settings.card_enabled = card.unwrap_or(goodbye_settings.card_enabled);
+        goodbye_settings.modify_user_id = Some(create_user_id);
+        goodbye_settings.modify_date = Some(Utc::now().naive_utc().to_string());
+
+        goodbye_settings::update(db, goodbye_settings).await?;
+    } else {
+        // Create new goodbye settings if none exist
+        let goodbye_settings = entity::goodbye_settings::Model {
+            id: 0,
+            goodbye_channel: channel.map_or(0, Into::into),
+            chat_message: chat_message.unwrap_or_else(|| {
+                "Goodbye **{name}**, thanks for being part of {guild}".to_string()
+            }),
+            image_headline: image_headline.unwrap_or_else(|| "{name} left the server".to_string()),
+            image_subtext: image_subline
+                .unwrap_or_else(|| "We are {members} members now".to_string()),
+            back_banner: DEFAULT_BACK_BANNER,
+            front_banner: DEFAULT_FRONT_BANNER,
+            enabled: enabled.unwrap_or(false),
+            card_enabled: card.unwrap_or(false),
+            image_template: None,
+            create_user_id,
+            create_date: Utc::now().naive_utc().to_string(),
+            modify_date: None,
+            modify_user_id: None,
+        };
+
+        let goodbye_settings = goodbye_settings::create(db, goodbye_settings).await?;
+        guild.goodbye_settings_id = Some(goodbye_settings.id);
+
+        guild::update(db, &guild).await?;
+    }
+
+    Ok(guild)
+}
+
+/// Sets the layout of the farewell card.
+///
+/// This command accepts a TOML or JSON template file in the format of the welcome image
+/// templates. Sending the command without a file restores the default layout.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `file` - An optional template file.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn template(
+    ctx: Context<'_>,
+    #[description = "A TOML or JSON template file. Leave empty to restore the default layout"]
+    file: Option<serenity::Attachment>,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    let Some(mut goodbye_settings) = load_goodbye_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    let image_template = match file {
+        Some(file) => match read_template(&file, &ctx.data().image_generator).await? {
+            Ok(image_template) => Some(image_template),
+            Err(why) => {
+                ctx.send(
+                    CreateReply::default()
+                        .content(format!("The template is invalid: {why}"))
+                        .ephemeral(true),
+                )
+                .await?;
+
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    goodbye_settings.image_template = image_template;
+    goodbye_settings.modify_user_id = Some(ctx.author().id.into());
+    goodbye_settings.modify_date = Some(Utc::now().naive_utc().to_string());
+    goodbye_settings::update(db, goodbye_settings).await?;
+
+    ctx.send(
+        CreateReply::default()
+            .content("Template updated.")
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Retrieves the goodbye settings of the guild, asking to configure them if they are
+/// missing.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+///
+/// # Returns
+/// Returns the goodbye settings, or `None` if the guild has none.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+async fn load_goodbye_settings_or_reply(
+    ctx: Context<'_>,
+) -> Result<Option<entity::goodbye_settings::Model>, PoiseError> {
+    let db = &ctx.data().conn;
+
+    // Unwrap since this is a guild-only command
+    let guild_id = ctx.guild_id().unwrap();
+
+    let goodbye_settings = match guild::get_by_guild_id(db, guild_id.into()).await? {
+        Some(entity::guild::Model {
+            goodbye_settings_id: Some(settings_id),
+            ..
+        }) => goodbye_settings::get_one(db, settings_id).await?,
+        _ => None,
+    };
+
+    if goodbye_settings.is_none() {
+        ctx.send(
+            CreateReply::default()
+                .content("Please configure the goodbye message with `/goodbye settings` first.")
+                .ephemeral(true),
+        )
+        .await?;
+    }
+
+    Ok(goodbye_settings)
+}
@@ -1,3 +1,6 @@
+pub mod autorole;
+pub mod goodbye;
 pub mod moderation;
 pub mod version;
 pub mod welcome;
+pub mod welcome_variant;
@@ -1,12 +1,30 @@
 use chrono::Utc;
+use img_gen::{ImageGenerator, template::Template};
 use migration::sea_orm::DbConn;
 use poise::{
     CreateReply,
-    serenity_prelude::{self as serenity},
+    serenity_prelude::{self as serenity, CreateAllowedMentions},
 };
-use welcome_service::{guild, welcome_settings};
+use welcome_service::{guild, welcome_settings, welcome_variant};
 
-use crate::{Context, PoiseError};
+use super::welcome_variant::variant;
+use crate::{
+    Context, PoiseError,
+    banner::{DEFAULT_BACK_BANNER, DEFAULT_FRONT_BANNER, store_banner},
+    join_burst::{DEFAULT_BURST_INTERVAL, DEFAULT_BURST_MESSAGE, DEFAULT_BURST_THRESHOLD},
+    text_template::{BATCH_VARIABLES, TextTemplate, TextTemplateError, VARIABLES},
+    welcome::{ImageContext, image_variables, render_welcome},
+    welcome_variant::{RANDOM_MODE, active_variants, apply_variant, pick_random},
+};
+
+/// The maximum size of an uploaded welcome image template in bytes.
+const MAX_TEMPLATE_SIZE: u32 = 64 * 1024;
+/// The welcome DM of new welcome settings.
+const DEFAULT_DM_MESSAGE: &str =
+    "Welcome to **{guild}**, {name}! Please read the rules before you start chatting.";
+/// The message shown when an uploaded template references files on the server.
+const TEMPLATE_FILE_SOURCE: &str =
+    "Templates can only use the background, foreground and avatar images.";
 
 /// Commands for welcoming users with the welcome bot.
 ///
@@ -21,7 +39,16 @@ use crate::{Context, PoiseError};
     slash_command,
     guild_only,
     default_member_permissions = "ADMINISTRATOR",
-    subcommands("settings")
+    subcommands(
+        "settings",
+        "template",
+        "dm",
+        "burst",
+        "preview",
+        "background",
+        "foreground",
+        "variant"
+    )
 )]
 pub async fn welcome(ctx: Context<'_>) -> Result<(), PoiseError> {
     ctx.say("How did you manage to do this?").await?;
@@ -34,11 +61,14 @@ pub async fn welcome(ctx: Context<'_>) -> Result<(), PoiseError> {
 /// such as the welcome message, image headline, subline, and the channel where
 /// welcome messages are sent.
 ///
+/// All texts share the placeholders of [`VARIABLES`], filters and conditionals. Invalid
+/// texts are rejected with a message describing the problem.
+///
 /// # Arguments
 /// * `ctx` - The command context.
-/// * `chat_message` - An optional text for the chat welcome message. Placeholders: `{user}`, `{guild_name}`.
-/// * `image_headline` - An optional text for the image headline. Placeholders: `{name}`.
-/// * `image_subline` - An optional text for the image subline. Placeholders: `{members}`.
+/// * `chat_message` - An optional text for the chat welcome message.
+/// * `image_headline` - An optional text for the image headline.
+/// * `image_subline` - An optional text for the image subline.
 /// * `channel` - An optional text channel where welcome messages should be sent.
 /// * `enabled` - An optional flag to enable or disable welcome messages.
 ///
@@ -52,11 +82,11 @@ pub async fn welcome(ctx: Context<'_>) -> Result<(), PoiseError> {
 )]
 async fn settings(
     ctx: Context<'_>,
-    #[description = "The text of the chat welcome message. Placeholders: {user}, {guild_name}"]
+    #[description = "The text of the chat welcome message, e.g. Hey {mention}, welcome to {guild}"]
     chat_message: Option<String>,
-    #[description = "The text of the headline of the image. Placeholders: {name}"]
+    #[description = "The text of the headline of the image, e.g. {name} just joined"]
     image_headline: Option<String>,
-    #[description = "The text of the subline of the image. Placeholders: {members}"]
+    #[description = "The text of the subline of the image, e.g. You are our {members|ordinal} member"]
     image_subline: Option<String>,
     #[description = "The channel where to send welcome messages to"]
     #[channel_types("Text")]
@@ -65,6 +95,25 @@ async fn settings(
 ) -> Result<(), PoiseError> {
     let db = &ctx.data().conn;
 
+    let texts = [
+        ("chat message", &chat_message),
+        ("image headline", &image_headline),
+        ("image subline", &image_subline),
+    ];
+
+    for (field, text) in texts {
+        if let Some(Err(why)) = text.as_deref().map(|text| check_text(text, VARIABLES)) {
+            ctx.send(
+                CreateReply::default()
+                    .content(format!("The {field} is invalid: {why}"))
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    }
+
     // Unwrap since this is a guild-only command
     let discord_guild = ctx.guild().unwrap().clone();
     let author_id = ctx.author().id.into();
@@ -146,9 +195,17 @@ async fn update_welcome_settings(
                 .unwrap_or_else(|| "{name} just joined the server".to_string()),
             image_subtext: image_subline
                 .unwrap_or_else(|| "You are the #{members} member".to_string()),
-            back_banner: 1,
-            front_banner: 2,
+            back_banner: DEFAULT_BACK_BANNER,
+            front_banner: DEFAULT_FRONT_BANNER,
             enabled: enabled.unwrap_or(false),
+            image_template: None,
+            dm_enabled: false,
+            dm_message: DEFAULT_DM_MESSAGE.to_string(),
+            dm_card: false,
+            variant_mode: RANDOM_MODE.to_string(),
+            burst_threshold: DEFAULT_BURST_THRESHOLD,
+            burst_interval: DEFAULT_BURST_INTERVAL,
+            burst_message: DEFAULT_BURST_MESSAGE.to_string(),
             create_user_id,
             create_date: Utc::now().naive_utc().to_string(),
             modify_date: None,
@@ -163,3 +220,558 @@ async fn update_welcome_settings(
 
     Ok(guild)
 }
+
+/// Sets the layout of the welcome image.
+///
+/// This command accepts a TOML or JSON template file describing the layers,
+/// positions, fonts and colors of the welcome image. Sending the command without
+/// a file restores the default layout.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `file` - An optional template file.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn template(
+    ctx: Context<'_>,
+    #[description = "A TOML or JSON template file. Leave empty to restore the default layout"]
+    file: Option<serenity::Attachment>,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    let Some(mut welcome_settings) = load_welcome_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    let image_template = match file {
+        Some(file) => match read_template(&file, &ctx.data().image_generator).await? {
+            Ok(image_template) => Some(image_template),
+            Err(why) => {
+                ctx.send(
+                    CreateReply::default()
+                        .content(format!("The template is invalid: {why}"))
+                        .ephemeral(true),
+                )
+                .await?;
+
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    welcome_settings.image_template = image_template;
+    welcome_settings.modify_user_id = Some(ctx.author().id.into());
+    welcome_settings.modify_date = Some(Utc::now().naive_utc().to_string());
+    welcome_settings::update(db, welcome_settings).await?;
+
+    ctx.send(
+        CreateReply::default()
+            .content("Template updated.")
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Configures the welcome DM sent privately to new members.
+///
+/// The DM is sent in addition to the welcome message in the welcome channel, e.g.
+/// to share the rules and useful links. Members who do not accept direct messages
+/// are skipped.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `message` - An optional text for the DM, sharing the placeholders of [`VARIABLES`].
+/// * `enabled` - An optional flag to enable or disable the welcome DM.
+/// * `card` - An optional flag to attach the welcome image to the DM.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn dm(
+    ctx: Context<'_>,
+    #[description = "The text of the DM, e.g. Welcome to {guild}, {name}! Please read the rules"]
+    message: Option<String>,
+    #[description = "Enables or disables the welcome DM"] enabled: Option<bool>,
+    #[description = "Attaches the welcome image to the DM"] card: Option<bool>,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    if let Some(Err(why)) = message.as_deref().map(|text| check_text(text, VARIABLES)) {
+        ctx.send(
+            CreateReply::default()
+                .content(format!("The message is invalid: {why}"))
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    let Some(mut welcome_settings) = load_welcome_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    welcome_settings.dm_message = message.unwrap_or(welcome_settings.dm_message);
+    welcome_settings.dm_enabled = enabled.unwrap_or(welcome_settings.dm_enabled);
+    welcome_settings.dm_card = card.unwrap_or(welcome_settings.dm_card);
+    welcome_settings.modify_user_id = Some(ctx.author().id.into());
+    welcome_settings.modify_date = Some(Utc::now().naive_utc().to_string());
+    welcome_settings::update(db, welcome_settings).await?;
+
+    ctx.send(
+        CreateReply::default()
+            .content("Welcome DM updated.")
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Configures the batching of welcome messages during join floods.
+///
+/// Above the join rate, e.g. during a raid or after a shout-out, new members are
+/// welcomed together in a single message every few seconds instead of with a card
+/// each. Once the rate drops, members are welcomed individually again.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `threshold` - An optional number of joins per minute above which welcomes are
+///   batched, 0 disables batching.
+/// * `interval` - An optional number of seconds between batched welcomes.
+/// * `message` - An optional text of the batched welcome, with the variables
+///   `{mentions}`, `{count}`, `{members}` and `{guild}`.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn burst(
+    ctx: Context<'_>,
+    #[description = "The joins per minute above which welcomes are batched, 0 disables it"]
+    #[max = 1000]
+    threshold: Option<u32>,
+    #[description = "The seconds between batched welcomes"]
+    #[min = 10]
+    #[max = 600]
+    interval: Option<u32>,
+    #[description = "The text of the batched welcome, e.g. Welcome {mentions} to {guild}!"]
+    message: Option<String>,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    if let Some(Err(why)) = message
+        .as_deref()
+        .map(|text| check_text(text, BATCH_VARIABLES))
+    {
+        ctx.send(
+            CreateReply::default()
+                .content(format!("The message is invalid: {why}"))
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    let Some(mut welcome_settings) = load_welcome_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    if let Some(threshold) = threshold {
+        welcome_settings.burst_threshold = threshold as i32;
+    }
+    if let Some(interval) = interval {
+        welcome_settings.burst_interval = interval as i32;
+    }
+    if let Some(message) = message {
+        welcome_settings.burst_message = message;
+    }
+    welcome_settings.modify_user_id = Some(ctx.author().id.into());
+    welcome_settings.modify_date = Some(Utc::now().naive_utc().to_string());
+
+    let content = if welcome_settings.burst_threshold > 0 {
+        format!(
+            "Above {} joins per minute, welcomes are batched every {} seconds.",
+            welcome_settings.burst_threshold, welcome_settings.burst_interval
+        )
+    } else {
+        "Welcomes are never batched.".to_string()
+    };
+
+    welcome_settings::update(db, welcome_settings).await?;
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Previews the welcome of a member without posting it.
+///
+/// The welcome is rendered exactly like for a joining member and sent back as an
+/// ephemeral reply with the welcome image, the chat message and, if enabled, the DM.
+/// Without a variant name, one of the variants active today is picked at random.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `member` - An optional member to preview the welcome for, by default the caller.
+/// * `variant` - An optional name of the welcome variant to preview.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation, rendering or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn preview(
+    ctx: Context<'_>,
+    #[description = "The member to preview, by default yourself"] member: Option<serenity::Member>,
+    #[description = "The name of the variant to preview"] variant: Option<String>,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    let Some(welcome_settings) = load_welcome_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    let variant = match variant {
+        Some(name) => {
+            let variant = welcome_variant::get_all(db, welcome_settings.id)
+                .await?
+                .into_iter()
+                .find(|variant| variant.name == name.trim());
+
+            if variant.is_none() {
+                ctx.send(
+                    CreateReply::default()
+                        .content(format!("There is no variant **{}**.", name.trim()))
+                        .allowed_mentions(CreateAllowedMentions::new())
+                        .ephemeral(true),
+                )
+                .await?;
+
+                return Ok(());
+            }
+
+            variant
+        }
+        None => pick_random(&active_variants(db, welcome_settings.id).await?).cloned(),
+    };
+
+    let welcome_settings = match &variant {
+        Some(variant) => apply_variant(welcome_settings, variant),
+        None => welcome_settings,
+    };
+
+    let Some(image_context) = ImageContext::init(db, &welcome_settings).await? else {
+        ctx.send(
+            CreateReply::default()
+                .content("The background or foreground image of the welcome image is missing.")
+                .ephemeral(true),
+        )
+        .await?;
+
+        return Ok(());
+    };
+
+    let member = match member {
+        Some(member) => member,
+        None => ctx
+            .author_member()
+            .await
+            .ok_or("The member of the caller could not be retrieved.")?
+            .into_owned(),
+    };
+
+    // Rendering may take longer than the three seconds Discord waits for a response.
+    ctx.defer_ephemeral().await?;
+
+    let welcome = render_welcome(
+        ctx.serenity_context(),
+        ctx.data(),
+        &image_context,
+        &member,
+        &welcome_settings,
+        true,
+    )
+    .await?;
+
+    let mut content = String::new();
+    if let Some(variant) = &variant {
+        content.push_str(&format!("**Variant**\n{}\n\n", variant.name));
+    }
+    content.push_str(&format!("**Chat message**\n{}", welcome.chat_message));
+    if welcome_settings.dm_enabled {
+        content.push_str(&format!("\n\n**DM**\n{}", welcome.dm_message));
+    }
+    if welcome.attachment.is_none() {
+        content.push_str("\n\nThe welcome image is not available right now, try again later.");
+    }
+
+    let mut reply = CreateReply::default()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new())
+        .ephemeral(true);
+    if let Some(attachment) = welcome.attachment {
+        reply = reply.attachment(attachment);
+    }
+
+    ctx.send(reply).await?;
+
+    Ok(())
+}
+
+/// Sets the background image of the welcome image.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `file` - A PNG, JPEG, WebP or GIF image.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn background(
+    ctx: Context<'_>,
+    #[description = "A PNG, JPEG, WebP or GIF image drawn behind the avatar"]
+    file: serenity::Attachment,
+) -> Result<(), PoiseError> {
+    update_banner(ctx, &file, Banner::Background).await
+}
+
+/// Sets the foreground image of the welcome image.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `file` - A PNG, JPEG, WebP or GIF image, usually with transparent areas.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn foreground(
+    ctx: Context<'_>,
+    #[description = "A PNG, JPEG, WebP or GIF image drawn over the avatar"]
+    file: serenity::Attachment,
+) -> Result<(), PoiseError> {
+    update_banner(ctx, &file, Banner::Foreground).await
+}
+
+/// The banners of the welcome image.
+#[derive(Debug, Clone, Copy)]
+enum Banner {
+    /// The image drawn behind the avatar.
+    Background,
+    /// The image drawn over the avatar.
+    Foreground,
+}
+
+/// Stores an uploaded banner and links it to the welcome settings of the guild.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `file` - The uploaded banner.
+/// * `banner` - The banner to replace.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+async fn update_banner(
+    ctx: Context<'_>,
+    file: &serenity::Attachment,
+    banner: Banner,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    let author_id = ctx.author().id.into();
+
+    let Some(mut welcome_settings) = load_welcome_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    // Downloading and checking the image may take longer than Discord waits for a response.
+    ctx.defer_ephemeral().await?;
+
+    let image = match store_banner(db, file, author_id).await? {
+        Ok(image) => image,
+        Err(why) => {
+            ctx.send(
+                CreateReply::default()
+                    .content(format!("The image is invalid: {why}"))
+                    .ephemeral(true),
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    match banner {
+        Banner::Background => welcome_settings.back_banner = image.id,
+        Banner::Foreground => welcome_settings.front_banner = image.id,
+    }
+    welcome_settings.modify_user_id = Some(author_id);
+    welcome_settings.modify_date = Some(Utc::now().naive_utc().to_string());
+    welcome_settings::update(db, welcome_settings).await?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "The {} is updated, check it with `/welcome preview`.",
+                match banner {
+                    Banner::Background => "background",
+                    Banner::Foreground => "foreground",
+                }
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Retrieves the welcome settings of the guild, asking to configure them if they are
+/// missing.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+///
+/// # Returns
+/// Returns the welcome settings, or `None` if the guild has none.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+pub(super) async fn load_welcome_settings_or_reply(
+    ctx: Context<'_>,
+) -> Result<Option<entity::welcome_settings::Model>, PoiseError> {
+    let db = &ctx.data().conn;
+
+    // Unwrap since this is a guild-only command
+    let guild_id = ctx.guild_id().unwrap();
+
+    let welcome_settings = match guild::get_by_guild_id(db, guild_id.into()).await? {
+        Some(entity::guild::Model {
+            welcome_settings_id: Some(settings_id),
+            ..
+        }) => welcome_settings::get_one(db, settings_id).await?,
+        _ => None,
+    };
+
+    if welcome_settings.is_none() {
+        ctx.send(
+            CreateReply::default()
+                .content("Please configure the welcome message with `/welcome settings` first.")
+                .ephemeral(true),
+        )
+        .await?;
+    }
+
+    Ok(welcome_settings)
+}
+
+/// Downloads and validates an uploaded welcome image template.
+///
+/// # Arguments
+/// * `file` - The uploaded template file.
+/// * `image_generator` - The image generator used to check the referenced fonts.
+///
+/// # Returns
+/// Returns the template text if it is valid, or a message describing the problem.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if downloading the file fails.
+#[fastrace::trace]
+pub(super) async fn read_template(
+    file: &serenity::Attachment,
+    image_generator: &ImageGenerator,
+) -> Result<Result<String, String>, PoiseError> {
+    if file.size > MAX_TEMPLATE_SIZE {
+        return Ok(Err(format!(
+            "The file is larger than {} KiB.",
+            MAX_TEMPLATE_SIZE / 1024
+        )));
+    }
+
+    let Ok(content) = String::from_utf8(file.download().await?) else {
+        return Ok(Err("The file is not valid UTF-8 text.".to_string()));
+    };
+
+    let image_template = match Template::parse(&content) {
+        Ok(image_template) => image_template,
+        Err(why) => return Ok(Err(why.to_string())),
+    };
+
+    if image_template.references_files() {
+        return Ok(Err(TEMPLATE_FILE_SOURCE.to_string()));
+    }
+
+    let variables = image_variables();
+    if let Some(why) = image_template
+        .texts()
+        .find_map(|text| check_text(text, &variables).err())
+    {
+        return Ok(Err(why));
+    }
+
+    if let Some(font) = image_template
+        .fonts()
+        .find(|font| !image_generator.has_font(font))
+    {
+        return Ok(Err(format!(
+            "The font '{font}' is not available. Available fonts: {}",
+            image_generator.font_names().join(", ")
+        )));
+    }
+
+    Ok(Ok(content))
+}
+
+/// Checks whether a text is a valid text template.
+///
+/// # Arguments
+/// * `text` - The text containing placeholders.
+/// * `variables` - The names of the variables the text may use.
+///
+/// # Returns
+/// Returns a message describing the problem if the text is invalid.
+pub(super) fn check_text(text: &str, variables: &[&str]) -> Result<(), String> {
+    match TextTemplate::parse(text, variables) {
+        Ok(_) => Ok(()),
+        Err(why @ TextTemplateError::UnknownVariable { .. }) => Err(format!(
+            "{why} Available placeholders: {}",
+            variables.join(", ")
+        )),
+        Err(why) => Err(why.to_string()),
+    }
+}
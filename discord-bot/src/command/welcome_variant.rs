@@ -0,0 +1,421 @@
+use chrono::Utc;
+use poise::{
+    CreateReply,
+    serenity_prelude::{self as serenity, CreateAllowedMentions},
+};
+use welcome_service::{welcome_settings, welcome_variant};
+
+use super::welcome::{check_text, load_welcome_settings_or_reply};
+use crate::{
+    Context, PoiseError,
+    banner::store_banner,
+    text_template::VARIABLES,
+    welcome_variant::{RANDOM_MODE, ROUND_ROBIN_MODE, is_active, parse_day},
+};
+
+/// Commands for rotating between several welcomes.
+///
+/// This command serves as the entry point for welcome variant subcommands.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if sending the response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR",
+    subcommands("add", "remove", "list", "mode")
+)]
+pub(super) async fn variant(ctx: Context<'_>) -> Result<(), PoiseError> {
+    ctx.say("How did you manage to do this?").await?;
+    Ok(())
+}
+
+#[derive(Debug, poise::ChoiceParameter)]
+pub enum VariantMode {
+    #[name = "Random"]
+    Random,
+    #[name = "Round-robin"]
+    RoundRobin,
+}
+
+impl VariantMode {
+    /// Returns the value of the mode stored in the welcome settings.
+    fn as_str(&self) -> &'static str {
+        match self {
+            VariantMode::Random => RANDOM_MODE,
+            VariantMode::RoundRobin => ROUND_ROBIN_MODE,
+        }
+    }
+}
+
+/// The texts, images and days a new welcome variant sets.
+struct VariantOptions {
+    /// An optional text for the chat welcome message.
+    chat_message: Option<String>,
+    /// An optional text for the image headline.
+    image_headline: Option<String>,
+    /// An optional text for the image subline.
+    image_subline: Option<String>,
+    /// An optional background image.
+    background: Option<serenity::Attachment>,
+    /// An optional foreground image.
+    foreground: Option<serenity::Attachment>,
+    /// An optional weight, how often the variant is picked compared to others.
+    weight: Option<u32>,
+    /// An optional first day of the variant in the format `MM-DD`.
+    start: Option<String>,
+    /// An optional last day of the variant in the format `MM-DD`.
+    end: Option<String>,
+}
+
+/// Adds a welcome variant, which replaces texts or images of the welcome.
+///
+/// Each join picks one of the variants active that day. Texts and images the variant
+/// does not set are taken from the welcome settings, and if no variant is active the
+/// welcome settings are used as they are. Adding a variant again replaces it.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `name` - The name of the variant.
+/// * `chat_message` - An optional text for the chat welcome message.
+/// * `image_headline` - An optional text for the image headline.
+/// * `image_subline` - An optional text for the image subline.
+/// * `background` - An optional background image.
+/// * `foreground` - An optional foreground image.
+/// * `weight` - An optional weight, how often the variant is picked compared to others.
+/// * `start` - An optional first day of the variant in the format `MM-DD`.
+/// * `end` - An optional last day of the variant in the format `MM-DD`.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+// Poise takes every option of a slash command as a parameter.
+#[allow(clippy::too_many_arguments)]
+async fn add(
+    ctx: Context<'_>,
+    #[description = "The name of the variant, e.g. Halloween"]
+    #[max_length = 50]
+    name: String,
+    #[description = "The text of the chat welcome message"] chat_message: Option<String>,
+    #[description = "The text of the headline of the image"] image_headline: Option<String>,
+    #[description = "The text of the subline of the image"] image_subline: Option<String>,
+    #[description = "An image drawn behind the avatar"] background: Option<serenity::Attachment>,
+    #[description = "An image drawn over the avatar"] foreground: Option<serenity::Attachment>,
+    #[description = "How often the variant is picked compared to others, by default 1"]
+    #[min = 1]
+    #[max = 100]
+    weight: Option<u32>,
+    #[description = "The first day of the variant every year, e.g. 10-01"] start: Option<String>,
+    #[description = "The last day of the variant every year, e.g. 10-31"] end: Option<String>,
+) -> Result<(), PoiseError> {
+    let options = VariantOptions {
+        chat_message,
+        image_headline,
+        image_subline,
+        background,
+        foreground,
+        weight,
+        start,
+        end,
+    };
+
+    add_variant(ctx, name, options).await
+}
+
+/// Stores a welcome variant, replacing the variant with the same name.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `name` - The name of the variant.
+/// * `options` - The texts, images and days the variant sets.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+async fn add_variant(
+    ctx: Context<'_>,
+    name: String,
+    options: VariantOptions,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+    let author_id = ctx.author().id.into();
+
+    if let Err(why) = check_variant(&name, &options) {
+        ctx.send(CreateReply::default().content(why).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    let Some(welcome_settings) = load_welcome_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    // Downloading and checking the images may take longer than Discord waits for a response.
+    ctx.defer_ephemeral().await?;
+
+    let mut banners = [None, None];
+    for (banner, file) in banners
+        .iter_mut()
+        .zip([&options.background, &options.foreground])
+    {
+        let Some(file) = file else {
+            continue;
+        };
+
+        match store_banner(db, file, author_id).await? {
+            Ok(image) => *banner = Some(image.id),
+            Err(why) => {
+                ctx.send(
+                    CreateReply::default()
+                        .content(format!("The image '{}' is invalid: {why}", file.filename))
+                        .ephemeral(true),
+                )
+                .await?;
+
+                return Ok(());
+            }
+        }
+    }
+    let [back_banner, front_banner] = banners;
+
+    let name = name.trim().to_string();
+    welcome_variant::delete_by_name(db, welcome_settings.id, &name).await?;
+    welcome_variant::create(
+        db,
+        entity::welcome_variant::Model {
+            id: 0,
+            welcome_settings_id: welcome_settings.id,
+            name: name.clone(),
+            chat_message: options.chat_message,
+            image_headline: options.image_headline,
+            image_subtext: options.image_subline,
+            back_banner,
+            front_banner,
+            weight: options.weight.unwrap_or(1) as i32,
+            start_date: options.start,
+            end_date: options.end,
+            create_user_id: author_id,
+            create_date: Utc::now(),
+        },
+    )
+    .await?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "The variant **{name}** is added, check it with `/welcome preview`."
+            ))
+            .allowed_mentions(CreateAllowedMentions::new())
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Removes a welcome variant.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `name` - The name of the variant.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn remove(
+    ctx: Context<'_>,
+    #[description = "The name of the variant"] name: String,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    let Some(welcome_settings) = load_welcome_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    let name = name.trim();
+    let removed = welcome_variant::delete_by_name(db, welcome_settings.id, name)
+        .await?
+        .rows_affected
+        > 0;
+
+    let content = if removed {
+        format!("The variant **{name}** is removed.")
+    } else {
+        format!("There is no variant **{name}**.")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new())
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Lists the welcome variants and whether they are active today.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn list(ctx: Context<'_>) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    let Some(welcome_settings) = load_welcome_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    let variants = welcome_variant::get_all(db, welcome_settings.id).await?;
+    let today = Utc::now().date_naive();
+
+    let content = if variants.is_empty() {
+        "There are no welcome variants.".to_string()
+    } else {
+        let mode = if welcome_settings.variant_mode == ROUND_ROBIN_MODE {
+            "in turn"
+        } else {
+            "at random"
+        };
+
+        let lines = variants
+            .iter()
+            .map(|variant| {
+                let days = match (&variant.start_date, &variant.end_date) {
+                    (Some(start), Some(end)) => format!(", from {start} to {end}"),
+                    _ => String::new(),
+                };
+                let state = if is_active(variant, today) {
+                    "active"
+                } else {
+                    "inactive"
+                };
+
+                format!(
+                    "- **{}**, weight {}{days}, {state}",
+                    variant.name, variant.weight
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!("Active variants are picked {mode}.\n{lines}")
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .content(content)
+            .allowed_mentions(CreateAllowedMentions::new())
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Sets how the welcome variant of a joining member is picked.
+///
+/// # Arguments
+/// * `ctx` - The command context.
+/// * `mode` - Random picks by weight, round-robin picks each variant in turn as often as
+///   its weight.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any database operation or response fails.
+#[fastrace::trace]
+#[poise::command(
+    slash_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR"
+)]
+async fn mode(
+    ctx: Context<'_>,
+    #[description = "Pick variants at random or in turn"] mode: VariantMode,
+) -> Result<(), PoiseError> {
+    let db = &ctx.data().conn;
+
+    let Some(mut welcome_settings) = load_welcome_settings_or_reply(ctx).await? else {
+        return Ok(());
+    };
+
+    welcome_settings.variant_mode = mode.as_str().to_string();
+    welcome_settings.modify_user_id = Some(ctx.author().id.into());
+    welcome_settings.modify_date = Some(Utc::now().naive_utc().to_string());
+    welcome_settings::update(db, welcome_settings).await?;
+
+    ctx.send(
+        CreateReply::default()
+            .content("Settings updated.")
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Checks the options of a new welcome variant.
+///
+/// # Arguments
+/// * `name` - The name of the variant.
+/// * `options` - The texts, images and days of the variant.
+///
+/// # Returns
+/// Returns a message describing the problem if the options are invalid.
+fn check_variant(name: &str, options: &VariantOptions) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("The name of the variant must not be empty.".to_string());
+    }
+
+    let texts = [
+        ("chat message", &options.chat_message),
+        ("image headline", &options.image_headline),
+        ("image subline", &options.image_subline),
+    ];
+    let has_banner = options.background.is_some() || options.foreground.is_some();
+
+    if texts.iter().all(|(_, text)| text.is_none()) && !has_banner {
+        return Err("The variant must set at least one text or image.".to_string());
+    }
+
+    for (field, text) in texts {
+        if let Some(Err(why)) = text.as_deref().map(|text| check_text(text, VARIABLES)) {
+            return Err(format!("The {field} is invalid: {why}"));
+        }
+    }
+
+    match (options.start.as_deref(), options.end.as_deref()) {
+        (None, None) => Ok(()),
+        (Some(start), Some(end)) => {
+            for day in [start, end] {
+                if parse_day(day).is_none() {
+                    return Err(format!(
+                        "'{day}' is not a valid day, use the format MM-DD, e.g. 10-31."
+                    ));
+                }
+            }
+            Ok(())
+        }
+        _ => Err("Please set both the first and the last day of the variant.".to_string()),
+    }
+}
@@ -26,4 +26,10 @@ pub enum Error {
     /// This error is typically caused by issues in the `img_gen` module.
     #[error("An error occurred during image generation.")]
     ImageGen(#[from] img_gen::error::Error),
+
+    /// Error that occurs when the HTTP client cannot be created.
+    ///
+    /// This error is typically caused by an unavailable TLS backend.
+    #[error("An error occurred while creating the HTTP client.")]
+    Http(#[from] reqwest::Error),
 }
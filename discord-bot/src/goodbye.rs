@@ -0,0 +1,122 @@
+use log::info;
+use poise::serenity_prelude::{self as serenity, ChannelId, CreateMessage};
+use welcome_service::{goodbye_settings, guild};
+
+use crate::{
+    Data, PoiseError,
+    text_template::member_variables,
+    util::guild_member_count,
+    welcome::{ImageContext, render_card},
+};
+
+/// Handles a member leaving the guild.
+///
+/// This function sends the goodbye message of the guild if goodbye settings are enabled,
+/// together with a farewell card if the card is enabled.
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `data` - The shared bot data.
+/// * `guild_id` - The guild the member left.
+/// * `user` - The user who left the guild.
+/// * `member` - The member data of the user, if it was cached.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any operation fails.
+#[fastrace::trace]
+pub async fn handle_member_leave(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: &serenity::GuildId,
+    user: &serenity::User,
+    member: Option<&serenity::Member>,
+) -> Result<(), PoiseError> {
+    info!("User left: Id:'{}', name:'{}'.", user.id, user.name);
+
+    if user.bot {
+        return Ok(());
+    }
+
+    let db = &data.conn;
+
+    let Some(guild) = guild::get_by_guild_id(db, (*guild_id).into()).await? else {
+        return Ok(());
+    };
+
+    let Some(settings_id) = guild.goodbye_settings_id else {
+        return Ok(());
+    };
+
+    let Some(goodbye_settings) = goodbye_settings::get_one(db, settings_id).await? else {
+        return Ok(());
+    };
+
+    if !goodbye_settings.enabled {
+        return Ok(());
+    }
+
+    send_goodbye_message(ctx, data, *guild_id, user, member, &goodbye_settings).await
+}
+
+/// Sends a goodbye message to the goodbye channel.
+///
+/// The farewell card is rendered on the render pool like the welcome image. If the pool
+/// is saturated or the card cannot be rendered, the message is sent without image.
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `data` - The shared bot data.
+/// * `guild_id` - The guild the member left.
+/// * `user` - The user who left the guild.
+/// * `member` - The member data of the user, if it was cached.
+/// * `goodbye_settings` - The goodbye settings model.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if any operation fails.
+#[fastrace::trace]
+async fn send_goodbye_message(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild_id: serenity::GuildId,
+    user: &serenity::User,
+    member: Option<&serenity::Member>,
+    goodbye_settings: &entity::goodbye_settings::Model,
+) -> Result<(), PoiseError> {
+    let (guild_name, members) = guild_member_count(ctx, guild_id).await?;
+
+    let display_name = member.map_or_else(|| user.display_name(), serenity::Member::display_name);
+    let variables = member_variables(
+        user,
+        display_name,
+        member.and_then(|member| member.joined_at),
+        &guild_name,
+        members,
+    );
+
+    let channel = ChannelId::new(goodbye_settings.goodbye_channel as u64);
+    let message = variables.render(&goodbye_settings.chat_message);
+
+    let mut message = CreateMessage::new().content(message);
+
+    if goodbye_settings.card_enabled {
+        let image_context = ImageContext::load(
+            &data.conn,
+            goodbye_settings.back_banner,
+            goodbye_settings.front_banner,
+            &goodbye_settings.image_headline,
+            &goodbye_settings.image_subtext,
+            goodbye_settings.image_template.as_deref(),
+        )
+        .await?;
+
+        if let Some(image_context) = image_context
+            && let Some(attachment) =
+                render_card(data, &image_context, guild_id, user, member, &variables).await?
+        {
+            message = message.add_file(attachment);
+        }
+    }
+
+    channel.send_message(&ctx.http, message).await?;
+    Ok(())
+}
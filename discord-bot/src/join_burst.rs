@@ -0,0 +1,264 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+use poise::serenity_prelude::{
+    self as serenity, ChannelId, CreateAllowedMentions, CreateMessage, GuildId, UserId,
+};
+
+use crate::{PoiseError, text_template::batch_variables, util::guild_member_count};
+
+/// The number of joins per minute above which welcomes are batched, unless configured.
+pub const DEFAULT_BURST_THRESHOLD: i32 = 10;
+/// The seconds between batched welcomes, unless configured.
+pub const DEFAULT_BURST_INTERVAL: i32 = 30;
+/// The text of the batched welcome, unless configured.
+pub const DEFAULT_BURST_MESSAGE: &str = "Welcome {mentions} to **{guild}**!";
+/// The period the join rate is measured over.
+const JOIN_RATE_WINDOW: Duration = Duration::from_secs(60);
+/// The maximum number of members mentioned in a batched welcome.
+const MAX_MENTIONS: usize = 10;
+
+/// How the welcome of a joining member is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Admission {
+    /// The member is welcomed with an own message.
+    Individual,
+    /// The member is welcomed in the next batched welcome.
+    Batched {
+        /// Whether the join started batching, so the batched welcomes need to be sent.
+        started: bool,
+    },
+}
+
+/// The welcome settings a flood is batched with, taken when batching starts.
+#[derive(Debug, Clone)]
+struct BatchSettings {
+    /// The welcome channel.
+    channel: ChannelId,
+    /// The number of joins per minute above which welcomes are batched.
+    threshold: u32,
+    /// The time between batched welcomes.
+    interval: Duration,
+    /// The text template of the batched welcome.
+    message: String,
+}
+
+/// The joins of a guild.
+#[derive(Debug, Default)]
+struct GuildJoins {
+    /// The times of the joins within the rate window.
+    recent: VecDeque<Instant>,
+    /// The members waiting for the next batched welcome, or `None` if welcomes are
+    /// sent individually.
+    batch: Option<Vec<UserId>>,
+}
+
+impl GuildJoins {
+    /// Forgets the joins which are older than the rate window.
+    fn prune(&mut self, now: Instant) {
+        while let Some(time) = self.recent.front() {
+            if now.duration_since(*time) < JOIN_RATE_WINDOW {
+                break;
+            }
+            self.recent.pop_front();
+        }
+    }
+}
+
+/// Detects join floods, e.g. raids or shout-outs, and coalesces their welcomes.
+///
+/// Above the join rate configured for a guild, new members are collected and welcomed
+/// together every few seconds instead of with a card each. Once the rate drops, members
+/// are welcomed individually again. The state is kept in memory only.
+#[derive(Debug, Clone, Default)]
+pub struct JoinBursts {
+    /// The joins of each guild.
+    guilds: Arc<Mutex<HashMap<GuildId, GuildJoins>>>,
+}
+
+impl JoinBursts {
+    /// Records the join of a member and decides whether the welcome message is batched.
+    ///
+    /// If the join starts a flood, the batched welcomes are sent in the background until
+    /// the join rate drops again.
+    ///
+    /// # Arguments
+    /// * `ctx` - The Serenity context.
+    /// * `new_member` - The new member who joined the guild.
+    /// * `welcome_settings` - The welcome settings model.
+    ///
+    /// # Returns
+    /// Returns `true` if the member is welcomed in a batched welcome instead of an own
+    /// message.
+    #[fastrace::trace]
+    pub fn admit(
+        &self,
+        ctx: &serenity::Context,
+        new_member: &serenity::Member,
+        welcome_settings: &entity::welcome_settings::Model,
+    ) -> bool {
+        if !welcome_settings.enabled || welcome_settings.burst_threshold <= 0 {
+            return false;
+        }
+
+        let threshold = welcome_settings.burst_threshold as u32;
+        match self.record(new_member.guild_id, new_member.user.id, threshold) {
+            Admission::Individual => false,
+            Admission::Batched { started } => {
+                if started {
+                    let settings = BatchSettings {
+                        channel: ChannelId::new(welcome_settings.welcome_channel as u64),
+                        threshold,
+                        interval: Duration::from_secs(welcome_settings.burst_interval.max(1) as u64),
+                        message: welcome_settings.burst_message.clone(),
+                    };
+
+                    tokio::spawn(self.clone().send_batches(
+                        ctx.clone(),
+                        new_member.guild_id,
+                        settings,
+                    ));
+                }
+                true
+            }
+        }
+    }
+
+    /// Records a join and decides how the member is welcomed.
+    ///
+    /// # Arguments
+    /// * `guild_id` - The guild the member joined.
+    /// * `user_id` - The member who joined.
+    /// * `threshold` - The number of joins per minute above which welcomes are batched.
+    fn record(&self, guild_id: GuildId, user_id: UserId, threshold: u32) -> Admission {
+        let now = Instant::now();
+        let mut guilds = self.guilds.lock().unwrap_or_else(PoisonError::into_inner);
+        let joins = guilds.entry(guild_id).or_default();
+
+        joins.prune(now);
+        joins.recent.push_back(now);
+
+        if let Some(batch) = &mut joins.batch {
+            batch.push(user_id);
+            return Admission::Batched { started: false };
+        }
+
+        if joins.recent.len() > threshold as usize {
+            joins.batch = Some(vec![user_id]);
+            return Admission::Batched { started: true };
+        }
+
+        Admission::Individual
+    }
+
+    /// Takes the members waiting for the batched welcome.
+    ///
+    /// If the join rate has dropped to the threshold, batching ends and members joining
+    /// afterwards are welcomed individually.
+    ///
+    /// # Returns
+    /// Returns the waiting members and whether batching has ended.
+    fn drain(&self, guild_id: GuildId, threshold: u32) -> (Vec<UserId>, bool) {
+        let now = Instant::now();
+        let mut guilds = self.guilds.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(joins) = guilds.get_mut(&guild_id) else {
+            return (Vec::new(), true);
+        };
+
+        joins.prune(now);
+        let members = joins.batch.as_mut().map(std::mem::take).unwrap_or_default();
+
+        if joins.recent.len() > threshold as usize {
+            return (members, false);
+        }
+
+        joins.batch = None;
+        if joins.recent.is_empty() {
+            guilds.remove(&guild_id);
+        }
+
+        (members, true)
+    }
+
+    /// Sends the batched welcomes of a guild until the join rate drops.
+    ///
+    /// Every interval, the members who joined since the last batched welcome are
+    /// welcomed in a single message to the welcome channel.
+    ///
+    /// # Arguments
+    /// * `ctx` - The Serenity context.
+    /// * `guild_id` - The guild to send the batched welcomes for.
+    /// * `settings` - The welcome settings of the flood.
+    #[fastrace::trace]
+    async fn send_batches(
+        self,
+        ctx: serenity::Context,
+        guild_id: GuildId,
+        settings: BatchSettings,
+    ) {
+        info!("Join flood in guild '{guild_id}', batching welcomes.");
+
+        loop {
+            tokio::time::sleep(settings.interval).await;
+
+            let (members, ended) = self.drain(guild_id, settings.threshold);
+            if !members.is_empty()
+                && let Err(why) = send_batched_welcome(&ctx, guild_id, &settings, &members).await
+            {
+                warn!("Failed to send the batched welcome in guild '{guild_id}': {why}");
+            }
+
+            if ended {
+                info!("Join flood in guild '{guild_id}' is over, welcoming members individually.");
+                return;
+            }
+        }
+    }
+}
+
+/// Sends a single welcome for several members, e.g. "Welcome @a, @b and 12 others".
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `guild_id` - The guild the members joined.
+/// * `settings` - The welcome settings of the flood.
+/// * `members` - The members to welcome.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if retrieving the guild or sending the message fails.
+#[fastrace::trace]
+async fn send_batched_welcome(
+    ctx: &serenity::Context,
+    guild_id: GuildId,
+    settings: &BatchSettings,
+    members: &[UserId],
+) -> Result<(), PoiseError> {
+    let (guild_name, member_count) = guild_member_count(ctx, guild_id).await?;
+
+    let mentioned = &members[..members.len().min(MAX_MENTIONS)];
+    let mentions: Vec<String> = mentioned
+        .iter()
+        .map(|user_id| format!("<@{user_id}>"))
+        .collect();
+
+    let names = match members.len() - mentioned.len() {
+        0 => match mentions.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} and {last}", rest.join(", ")),
+            _ => mentions.join(", "),
+        },
+        1 => format!("{} and 1 other", mentions.join(", ")),
+        others => format!("{} and {others} others", mentions.join(", ")),
+    };
+
+    let variables = batch_variables(&names, members.len(), &guild_name, member_count);
+    let message = CreateMessage::new()
+        .content(variables.render(&settings.message))
+        .allowed_mentions(CreateAllowedMentions::new().users(mentioned.iter().copied()));
+
+    settings.channel.send_message(&ctx.http, message).await?;
+    Ok(())
+}
@@ -1,23 +1,41 @@
+mod auto_role;
+mod avatar;
+mod banner;
 pub mod command;
 mod embed;
 pub mod error;
+mod goodbye;
 pub mod interaction;
+mod join_burst;
 mod moderation;
+mod render;
+mod text_template;
 pub mod util;
 mod welcome;
+mod welcome_variant;
 
-use command::{moderation::moderation, version::version, welcome::welcome};
+use std::sync::Arc;
+
+use auto_role::assign_join_roles_after_screening;
+use avatar::AvatarService;
+use command::{
+    autorole::autorole, goodbye::goodbye, moderation::moderation, version::version,
+    welcome::welcome,
+};
 use corelib::logging::setup_observability;
 use error::Error;
+use goodbye::handle_member_leave;
 use img_gen::ImageGenerator;
+use join_burst::JoinBursts;
 use migration::{
     Migrator, MigratorTrait,
     sea_orm::{Database, DatabaseConnection},
 };
 use moderation::{handle_suspicious_user, update_ban_log};
 use poise::serenity_prelude::{self as serenity};
-use tempfile::{TempDir, tempdir};
-use welcome::{handle_member_join, setup_image_generator};
+use render::RenderPool;
+use welcome::{handle_member_join, setup_avatar_service, setup_image_generator};
+use welcome_variant::VariantPicker;
 
 use crate::moderation::send_audit_log_entry;
 
@@ -32,15 +50,21 @@ pub struct Data {
     /// The database connection.
     conn: DatabaseConnection,
     /// The image generator for creating welcome images.
-    image_generator: ImageGenerator,
-    /// A temporary directory for storing files.
-    temp_dir: TempDir,
+    image_generator: Arc<ImageGenerator>,
+    /// The worker threads rendering welcome images.
+    render_pool: RenderPool,
+    /// The avatars shown on welcome and farewell cards.
+    avatars: AvatarService,
+    /// The picker of welcome variants, keeping the round-robin positions.
+    variants: VariantPicker,
+    /// The join flood detector batching welcome messages.
+    join_bursts: JoinBursts,
 }
 
 /// Handles events received from Discord.
 ///
-/// This function processes various events, such as member additions, member updates,
-/// and guild bans, and performs the appropriate actions.
+/// This function processes various events, such as member additions, member removals,
+/// member updates and guild bans, and performs the appropriate actions.
 ///
 /// # Arguments
 /// * `ctx` - The Serenity context.
@@ -61,11 +85,21 @@ async fn event_handler(
         serenity::FullEvent::GuildMemberAddition { new_member } => {
             handle_member_join(ctx, data, new_member).await
         }
+        serenity::FullEvent::GuildMemberRemoval {
+            guild_id,
+            user,
+            member_data_if_available,
+        } => {
+            handle_member_leave(ctx, data, guild_id, user, member_data_if_available.as_ref()).await
+        }
         serenity::FullEvent::GuildMemberUpdate {
-            old_if_available: _,
+            old_if_available,
             new,
             event,
-        } => handle_suspicious_user(ctx, data, new.as_ref(), event).await,
+        } => {
+            assign_join_roles_after_screening(ctx, data, old_if_available.as_ref(), event).await;
+            handle_suspicious_user(ctx, data, new.as_ref(), event).await
+        }
         serenity::FullEvent::GuildBanAddition {
             guild_id,
             banned_user,
@@ -87,18 +121,17 @@ async fn main() -> Result<(), Error> {
     let _guard = setup_observability("welcome_bot");
     log::info!("Starting welcome bot...");
 
-    // Create a temporary directory for storing files
-    let tmp_dir = tempdir().expect("Tempdir could not be created");
-
-    // Set up the image generator
-    let img_generator = setup_image_generator()?;
-
     // Load environment variables
     dotenvy::dotenv().ok();
     let token = std::env::var("WELCOMEBOT_TOKEN").expect("Missing WELCOMEBOT_TOKEN.");
     let db_url = std::env::var("WELCOME_DATABASE_URL")
         .expect("WELCOME_DATABASE_URL is not set in .env file");
 
+    // Set up the image generator
+    let img_generator = Arc::new(setup_image_generator()?);
+    let render_pool = RenderPool::from_env();
+    let avatars = setup_avatar_service()?;
+
     // Set up Discord gateway intents
     let intents =
         serenity::GatewayIntents::non_privileged() | serenity::GatewayIntents::GUILD_MEMBERS;
@@ -114,7 +147,7 @@ async fn main() -> Result<(), Error> {
     // Set up the Poise framework
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![version(), welcome(), moderation()],
+            commands: vec![version(), welcome(), goodbye(), autorole(), moderation()],
             event_handler: |ctx, event, framework, data| {
                 Box::pin(event_handler(ctx, event, framework, data))
             },
@@ -126,7 +159,10 @@ async fn main() -> Result<(), Error> {
                 Ok(Data {
                     conn,
                     image_generator: img_generator,
-                    temp_dir: tmp_dir,
+                    render_pool,
+                    avatars,
+                    variants: VariantPicker::default(),
+                    join_bursts: JoinBursts::default(),
                 })
             })
         })
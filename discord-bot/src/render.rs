@@ -0,0 +1,216 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+use log::{error, info, warn};
+use tokio::sync::oneshot;
+
+/// The number of queued renders at which new renders are rejected by default.
+const DEFAULT_HIGH_WATER_MARK: usize = 64;
+
+/// A unit of work executed on a render worker.
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// The queued jobs, guarded by a single lock.
+#[derive(Default)]
+struct Queue {
+    /// The pending jobs of every guild in submission order.
+    jobs: HashMap<u64, VecDeque<Job>>,
+    /// The guilds with pending jobs in the order they are served.
+    guilds: VecDeque<u64>,
+    /// The total number of pending jobs.
+    len: usize,
+}
+
+impl Queue {
+    /// Removes the next job, taking turns between the guilds.
+    ///
+    /// The guild of the returned job moves to the back of the line, so a raid on one
+    /// guild does not delay the welcome images of every other guild.
+    fn pop(&mut self) -> Option<Job> {
+        let guild_id = self.guilds.pop_front()?;
+        let jobs = self.jobs.get_mut(&guild_id)?;
+        let job = jobs.pop_front()?;
+
+        if jobs.is_empty() {
+            self.jobs.remove(&guild_id);
+        } else {
+            self.guilds.push_back(guild_id);
+        }
+
+        self.len -= 1;
+        Some(job)
+    }
+}
+
+/// The state shared between the pool and its workers.
+struct Shared {
+    /// The queued jobs.
+    queue: Mutex<Queue>,
+    /// Wakes up idle workers when a job is queued.
+    available: Condvar,
+}
+
+/// A fixed set of threads which render welcome images off the async runtime.
+///
+/// Rendering is CPU heavy, so running it inside the event handler would block the
+/// tokio workers and stall gateway handling during a raid. Jobs are queued per guild
+/// and served in turns. Once the queue reaches its high-water mark, new jobs are
+/// rejected so the caller can fall back to a text-only welcome.
+pub struct RenderPool {
+    /// The state shared with the workers.
+    shared: Arc<Shared>,
+    /// The number of queued jobs at which new jobs are rejected.
+    high_water_mark: usize,
+}
+
+impl RenderPool {
+    /// Creates a new `RenderPool` and starts its workers.
+    ///
+    /// # Arguments
+    /// * `workers` - The number of worker threads, at least one.
+    /// * `high_water_mark` - The number of queued jobs at which new jobs are rejected.
+    ///
+    /// # Panics
+    /// Panics if a worker thread cannot be spawned.
+    #[must_use]
+    pub fn new(workers: usize, high_water_mark: usize) -> Self {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue::default()),
+            available: Condvar::new(),
+        });
+
+        for index in 0..workers.max(1) {
+            let shared = shared.clone();
+
+            thread::Builder::new()
+                .name(format!("render-{index}"))
+                .spawn(move || work(&shared))
+                .expect("Failed to spawn a render worker.");
+        }
+
+        Self {
+            shared,
+            high_water_mark,
+        }
+    }
+
+    /// Creates a new `RenderPool` configured by environment variables.
+    ///
+    /// `WELCOMEBOT_RENDER_WORKERS` sets the number of workers, by default one less than
+    /// the number of CPUs and at least one. `WELCOMEBOT_RENDER_QUEUE` sets the high-water mark, by
+    /// default 64.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let env = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse::<usize>().ok())
+        };
+
+        let workers = match env("WELCOMEBOT_RENDER_WORKERS") {
+            Some(0) => {
+                warn!("WELCOMEBOT_RENDER_WORKERS is 0, starting one render worker instead.");
+                1
+            }
+            Some(workers) => workers,
+            None => thread::available_parallelism()
+                .map_or(1, |cpus| cpus.get().saturating_sub(1))
+                .max(1),
+        };
+        let high_water_mark = env("WELCOMEBOT_RENDER_QUEUE").unwrap_or(DEFAULT_HIGH_WATER_MARK);
+
+        info!("Starting {workers} render workers with a queue of {high_water_mark}.");
+        Self::new(workers, high_water_mark)
+    }
+
+    /// Checks whether the queue has reached its high-water mark.
+    ///
+    /// Callers can use it to skip preparing a render which would be rejected anyway.
+    #[must_use]
+    pub fn is_saturated(&self) -> bool {
+        self.lock().len >= self.high_water_mark
+    }
+
+    /// Queues a job for a guild.
+    ///
+    /// # Arguments
+    /// * `guild_id` - The guild the job belongs to, used to take turns between guilds.
+    /// * `job` - The work to run on a worker thread.
+    ///
+    /// # Returns
+    /// Returns a receiver for the result of the job, or `None` if the queue has
+    /// reached its high-water mark. The receiver fails if the job panics.
+    pub fn submit<T, F>(&self, guild_id: u64, job: F) -> Option<oneshot::Receiver<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+
+        {
+            let mut queue = self.lock();
+
+            if queue.len >= self.high_water_mark {
+                return None;
+            }
+
+            // Guilds without pending jobs have no entry, so they join the back of the line.
+            if !queue.jobs.contains_key(&guild_id) {
+                queue.guilds.push_back(guild_id);
+            }
+
+            queue
+                .jobs
+                .entry(guild_id)
+                .or_default()
+                .push_back(Box::new(move || {
+                    // The receiver is gone if the member's welcome was abandoned.
+                    let _ = sender.send(job());
+                }));
+            queue.len += 1;
+        }
+
+        self.shared.available.notify_one();
+        Some(receiver)
+    }
+
+    /// Locks the queue, ignoring panics of other threads holding the lock.
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.shared
+            .queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Runs queued jobs until the process exits.
+///
+/// # Arguments
+/// * `shared` - The state shared with the pool.
+fn work(shared: &Shared) {
+    loop {
+        let job = {
+            let mut queue = shared.queue.lock().unwrap_or_else(PoisonError::into_inner);
+
+            loop {
+                if let Some(job) = queue.pop() {
+                    break job;
+                }
+
+                queue = shared
+                    .available
+                    .wait(queue)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        };
+
+        // A panicking render must not take the worker down with it.
+        if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+            error!("A render job panicked.");
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+pub use img_gen::text_template::{TextTemplate, TextTemplateError, Variables};
+use poise::serenity_prelude::{Timestamp, User};
+
+/// The variables available in every welcome and goodbye text.
+pub const VARIABLES: &[&str] = &[
+    "mention",
+    "name",
+    "username",
+    "account_age",
+    "account_age_days",
+    "members",
+    "guild",
+    "join_date",
+    // Older names kept so existing messages continue to work.
+    "user",
+    "guild_name",
+];
+
+/// The variables available in the batched welcome sent during join floods.
+pub const BATCH_VARIABLES: &[&str] = &["mentions", "count", "members", "guild", "guild_name"];
+
+/// Creates the variables describing a member of a guild.
+///
+/// # Arguments
+/// * `user` - The user of the member.
+/// * `display_name` - The name of the member as shown in the guild.
+/// * `joined_at` - When the member joined the guild, if known.
+/// * `guild_name` - The name of the guild.
+/// * `members` - The number of members in the guild.
+#[must_use]
+pub fn member_variables(
+    user: &User,
+    display_name: &str,
+    joined_at: Option<Timestamp>,
+    guild_name: &str,
+    members: u64,
+) -> Variables {
+    let now = Utc::now().timestamp();
+    let age_secs = (now - user.created_at().unix_timestamp()).max(0);
+    let joined_at = joined_at.map_or(now, |joined_at| joined_at.unix_timestamp());
+    let mention = format!("<@{}>", user.id);
+
+    let mut variables = Variables::default();
+    variables
+        .insert("mention", mention.clone())
+        .insert("user", mention)
+        .insert("name", display_name)
+        .insert("username", user.name.clone())
+        .insert("account_age", format_age(age_secs))
+        .insert("account_age_days", (age_secs / 86_400).to_string())
+        .insert("members", members.to_string())
+        .insert("guild", guild_name)
+        .insert("guild_name", guild_name)
+        .insert("join_date", format_date(joined_at));
+
+    variables
+}
+
+/// Creates the variables describing the members welcomed in a batched welcome.
+///
+/// # Arguments
+/// * `mentions` - The mentions of the members, e.g. `@a, @b and 12 others`.
+/// * `count` - The number of members welcomed.
+/// * `guild_name` - The name of the guild.
+/// * `members` - The number of members in the guild.
+#[must_use]
+pub fn batch_variables(mentions: &str, count: usize, guild_name: &str, members: u64) -> Variables {
+    let mut variables = Variables::default();
+    variables
+        .insert("mentions", mentions)
+        .insert("count", count.to_string())
+        .insert("members", members.to_string())
+        .insert("guild", guild_name)
+        .insert("guild_name", guild_name);
+
+    variables
+}
+
+/// Formats a duration in seconds as the largest fitting unit, e.g. `3 months`.
+fn format_age(secs: i64) -> String {
+    let units = [
+        (365 * 86_400, "year"),
+        (30 * 86_400, "month"),
+        (86_400, "day"),
+        (3_600, "hour"),
+        (60, "minute"),
+    ];
+
+    let (count, unit) = units
+        .iter()
+        .map(|(length, unit)| (secs / length, *unit))
+        .find(|(count, _)| *count > 0)
+        .unwrap_or((secs, "second"));
+
+    if count == 1 {
+        format!("1 {unit}")
+    } else {
+        format!("{count} {unit}s")
+    }
+}
+
+/// Formats a unix timestamp as a date, e.g. `October 17, 2026`.
+fn format_date(secs: i64) -> String {
+    DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .format("%B %-d, %Y")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::{User, UserId};
+
+    use super::*;
+
+    #[test]
+    fn legacy_aliases() {
+        let mut user = User::default();
+        user.id = UserId::new(42);
+        user.name = "ferris".to_string();
+
+        let variables = member_variables(&user, "Ferris", None, "Rust", 7);
+
+        assert_eq!(
+            variables.render("Hey {user}, welcome to **{guild_name}**"),
+            "Hey <@42>, welcome to **Rust**"
+        );
+        assert_eq!(
+            variables.render("{user} {mention} {guild_name} {guild}"),
+            "<@42> <@42> Rust Rust"
+        );
+    }
+
+    #[test]
+    fn batch_variables_render() {
+        let variables = batch_variables("<@1>", 1, "Rust", 7);
+
+        assert_eq!(
+            variables.render("Welcome {mentions} to {guild}, {count} new"),
+            "Welcome <@1> to Rust, 1 new"
+        );
+    }
+}
@@ -32,3 +32,34 @@ pub async fn is_banned(
         .find(|x| x.user.id == member.user.id)
         .is_some())
 }
+
+/// Retrieves the name and member count of a guild.
+///
+/// The gateway cache keeps the member count of every guild up to date from
+/// `GuildCreate` and the member add and remove events, so usually no request is made.
+/// Guilds missing from the cache fall back to the approximate member count of the
+/// guild endpoint.
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `guild_id` - The ID of the guild.
+///
+/// # Returns
+/// Returns the name of the guild and its number of members.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if the guild is not cached and retrieving it fails.
+#[fastrace::trace]
+pub async fn guild_member_count(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+) -> Result<(String, u64), PoiseError> {
+    if let Some(guild) = ctx.cache.guild(guild_id) {
+        return Ok((guild.name.clone(), guild.member_count));
+    }
+
+    let partial_guild = ctx.http.get_guild_with_counts(guild_id).await?;
+    let members = partial_guild.approximate_member_count.unwrap_or_default();
+
+    Ok((partial_guild.name, members))
+}
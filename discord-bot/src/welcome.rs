@@ -1,25 +1,40 @@
-use std::path::{Path, PathBuf};
-
-use ab_glyph::{FontVec, PxScale};
-use image::{Rgba, imageops::FilterType};
-use img_gen::{ImageBuilder, ImageGenerator, Vec2, error::Error};
+use std::path::PathBuf;
+
+use ab_glyph::FontVec;
+use img_gen::{
+    ImageBuilder, ImageGenerator,
+    animation::AnimationOptions,
+    emoji::EmojiSet,
+    error::Error,
+    output::{self, OutputOptions},
+    source::ImageSource,
+    template::{Template, TemplateAssets},
+};
 use log::{info, warn};
 use migration::{DbErr, sea_orm::DbConn};
 use poise::serenity_prelude::{self as serenity, ChannelId, CreateAttachment, CreateMessage};
-use tempfile::TempDir;
-use tokio::{fs::File, io::AsyncWriteExt};
 use welcome_service::{guild, welcome_settings};
 
-use crate::{Data, PoiseError, moderation::send_suspicious_user_embed};
+use crate::{
+    Data, PoiseError,
+    auto_role::assign_join_roles,
+    avatar::AvatarService,
+    moderation::send_suspicious_user_embed,
+    text_template::{VARIABLES, Variables, member_variables},
+    util::guild_member_count,
+    welcome_variant::{active_variants, apply_variant},
+};
 
 static FIRA_SANS_BOLD: &str = "fsb";
 static FIRA_MONO_MEDIUM: &str = "fmm";
 const FIRA_SANS_BOLD_FILE: &[u8] = include_bytes!("../assets/FiraSans-Bold.ttf");
 const FIRA_MONO_MEDIUM_FILE: &[u8] = include_bytes!("../assets/FiraMono-Medium.ttf");
+const DEJAVU_SANS_FILE: &[u8] = include_bytes!("../assets/DejaVuSans.ttf");
+
+const DEFAULT_TEMPLATE: &str = include_str!("../assets/templates/welcome.toml");
 
-const IMAGE_POSITION: Vec2<i64> = Vec2::<i64>::new(322, 64);
-const BIG_SCALE: PxScale = PxScale { x: 40., y: 40. };
-const SMALL_SCALE: PxScale = PxScale { x: 24., y: 24. };
+/// The Discord error code returned when a user does not accept direct messages.
+const CANNOT_MESSAGE_USER: isize = 50007;
 
 /// Represents the context for generating welcome images.
 #[derive(Debug, Clone)]
@@ -32,6 +47,8 @@ pub struct ImageContext {
     pub headline_message: String,
     /// The subline message to display on the image.
     pub subline_message: String,
+    /// The layout of the welcome image.
+    pub template: Template,
 }
 
 impl ImageContext {
@@ -51,13 +68,45 @@ impl ImageContext {
         db: &DbConn,
         welcome_settings: &entity::welcome_settings::Model,
     ) -> Result<Option<Self>, DbErr> {
-        let Some(back_image_model) =
-            welcome_service::image::get_one(db, welcome_settings.back_banner).await?
-        else {
+        Self::load(
+            db,
+            welcome_settings.back_banner,
+            welcome_settings.front_banner,
+            &welcome_settings.image_headline,
+            &welcome_settings.image_subtext,
+            welcome_settings.image_template.as_deref(),
+        )
+        .await
+    }
+
+    /// Loads the `ImageContext` of a card from its banners, texts and template.
+    ///
+    /// # Arguments
+    /// * `db` - The database connection.
+    /// * `back_banner` - The ID of the background image.
+    /// * `front_banner` - The ID of the foreground image.
+    /// * `headline` - The headline message to display on the image.
+    /// * `subline` - The subline message to display on the image.
+    /// * `image_template` - The serialized template, or `None` for the default template.
+    ///
+    /// # Returns
+    /// Returns an `Option` containing the `ImageContext` if successful, or `None` if an image is missing.
+    ///
+    /// # Errors
+    /// Returns a [`DbErr`] if any database operation fails.
+    #[fastrace::trace]
+    pub async fn load(
+        db: &DbConn,
+        back_banner: i32,
+        front_banner: i32,
+        headline: &str,
+        subline: &str,
+        image_template: Option<&str>,
+    ) -> Result<Option<Self>, DbErr> {
+        let Some(back_image_model) = welcome_service::image::get_one(db, back_banner).await? else {
             return Ok(None);
         };
-        let Some(front_image_model) =
-            welcome_service::image::get_one(db, welcome_settings.front_banner).await?
+        let Some(front_image_model) = welcome_service::image::get_one(db, front_banner).await?
         else {
             return Ok(None);
         };
@@ -65,14 +114,59 @@ impl ImageContext {
         Ok(Some(Self {
             back_image: PathBuf::from(back_image_model.path),
             front_image: PathBuf::from(front_image_model.path),
-            headline_message: welcome_settings.image_headline.clone(),
-            subline_message: welcome_settings.image_subtext.clone(),
+            headline_message: headline.to_string(),
+            subline_message: subline.to_string(),
+            template: load_template(image_template),
         }))
     }
 }
 
+/// Loads the welcome image template of a guild.
+///
+/// Falls back to the default template if the guild has no template of its own
+/// or if the stored template can no longer be parsed.
+///
+/// # Arguments
+/// * `image_template` - The serialized template stored in the welcome settings.
+#[fastrace::trace]
+pub fn load_template(image_template: Option<&str>) -> Template {
+    if let Some(image_template) = image_template {
+        match Template::parse(image_template) {
+            Ok(template) => return template,
+            Err(why) => warn!("Stored welcome template is invalid, using default: {why}"),
+        }
+    }
+
+    default_template()
+}
+
+/// Returns the default welcome image template shipped with the bot.
+///
+/// # Panics
+/// Panics if the embedded default template is invalid.
+#[must_use]
+pub fn default_template() -> Template {
+    Template::from_toml(DEFAULT_TEMPLATE).expect("The default welcome template is invalid.")
+}
+
 /// Sets up the image generator by loading fonts.
 ///
+/// The bundled fonts are available as "fsb" and "fmm" and under their family and style
+/// names, e.g. "Fira Sans Bold". If `WELCOMEBOT_FONTS_DIR` points to a directory, every
+/// font file in it is added under its family and style name, so templates can use
+/// new fonts without a rebuild.
+///
+/// DejaVu Sans is registered as fallback font, so display names using Greek, Cyrillic,
+/// Arabic, Hebrew or symbol characters are not drawn as boxes. DejaVu Sans has no
+/// Chinese, Japanese or Korean glyphs and no color emoji, and no bundled font covers
+/// them, as CJK fonts are too large to ship with the bot:
+/// - If `WELCOMEBOT_FALLBACK_FONTS_DIR` points to a directory, every font file in it is
+///   added as fallback font after DejaVu Sans, e.g. `NotoSansCJK-Regular.ttc` for CJK
+///   display names. Without it, CJK characters are drawn as boxes.
+/// - If `WELCOMEBOT_EMOJI_DIR` points to a directory of Twemoji style PNGs, emoji are
+///   drawn in color from it. Without it, emoji are drawn with the fonts, monochrome if
+///   a font has them.
+///
 /// # Returns
 /// Returns an `ImageGenerator` instance if successful.
 ///
@@ -82,114 +176,117 @@ impl ImageContext {
 pub fn setup_image_generator() -> Result<ImageGenerator, Error> {
     let fira_sans_bold = FontVec::try_from_vec(FIRA_SANS_BOLD_FILE.to_vec())?;
     let fira_mono_medium = FontVec::try_from_vec(FIRA_MONO_MEDIUM_FILE.to_vec())?;
+    let dejavu_sans = FontVec::try_from_vec(DEJAVU_SANS_FILE.to_vec())?;
 
     let mut img_generator = ImageGenerator::new();
     img_generator.add_font(FIRA_SANS_BOLD, fira_sans_bold);
     img_generator.add_font(FIRA_MONO_MEDIUM, fira_mono_medium);
+    img_generator.add_named_font(FIRA_SANS_BOLD_FILE.to_vec())?;
+    img_generator.add_named_font(FIRA_MONO_MEDIUM_FILE.to_vec())?;
+    img_generator.add_fallback_font(dejavu_sans);
+
+    if let Ok(fonts_dir) = std::env::var("WELCOMEBOT_FONTS_DIR") {
+        match img_generator.load_font_dir(&fonts_dir) {
+            Ok(names) => info!(
+                "Loaded {} fonts from {fonts_dir}: {}",
+                names.len(),
+                names.join(", ")
+            ),
+            Err(why) => warn!("Failed to load fonts from {fonts_dir}: {why}"),
+        }
+    }
+
+    match std::env::var("WELCOMEBOT_FALLBACK_FONTS_DIR") {
+        Ok(fallback_dir) => match img_generator.load_fallback_font_dir(&fallback_dir) {
+            Ok(names) => info!(
+                "Loaded {} fallback fonts from {fallback_dir}: {}",
+                names.len(),
+                names.join(", ")
+            ),
+            Err(why) => warn!("Failed to load fallback fonts from {fallback_dir}: {why}"),
+        },
+        Err(_) => info!(
+            "WELCOMEBOT_FALLBACK_FONTS_DIR is not set, Chinese, Japanese and Korean characters are drawn as boxes."
+        ),
+    }
+
+    if let Ok(emoji_dir) = std::env::var("WELCOMEBOT_EMOJI_DIR") {
+        info!("Loading emoji images from {emoji_dir}.");
+        img_generator.set_emoji(EmojiSet::new(emoji_dir));
+    }
 
     Ok(img_generator)
 }
 
-/// Creates an `ImageBuilder` for generating welcome images.
-///
-/// # Arguments
-/// * `front_image_path` - The path to the foreground image.
-/// * `back_image_path` - The path to the background image.
-/// * `file_path` - The path to the user's avatar image.
-/// * `headline_message` - The headline message to display.
-/// * `subline_message` - The subline message to display.
-/// * `position` - The position of the avatar on the image.
-/// * `display_name` - The display name of the user.
-/// * `members` - The number of members in the guild.
-/// * `big_scale` - The font scale for the headline.
-/// * `small_scale` - The font scale for the subline.
+/// Sets up the avatar service, which draws initials avatars with the bundled Fira Sans Bold.
 ///
 /// # Returns
-/// Returns an `ImageBuilder` instance.
+/// Returns an `AvatarService` instance if successful.
+///
+/// # Errors
+/// Returns a [`crate::error::Error`] if loading the font or creating the HTTP client fails.
 #[fastrace::trace]
-fn create_image_builder(
-    front_image_path: impl AsRef<Path>,
-    back_image_path: impl AsRef<Path>,
-    file_path: impl AsRef<Path>,
-    headline_message: impl AsRef<str>,
-    subline_message: impl AsRef<str>,
-    position: Vec2<i64>,
-    display_name: impl AsRef<str>,
-    members: usize,
-    big_scale: PxScale,
-    small_scale: PxScale,
-) -> ImageBuilder {
-    let image_builder = ImageBuilder::new(back_image_path)
-        .add_image(&file_path, position.x, position.y)
-        .add_image(front_image_path, 0, 0)
-        .add_text(
-            &headline_message
-                .as_ref()
-                .replace("{name}", display_name.as_ref()),
-            450,
-            352,
-            big_scale,
-            FIRA_SANS_BOLD,
-            Rgba([34, 34, 34, 255]),
-            true,
-        )
-        .add_text(
-            &subline_message
-                .as_ref()
-                .replace("{members}", &members.to_string()),
-            450,
-            400,
-            small_scale,
-            FIRA_MONO_MEDIUM,
-            Rgba([59, 59, 59, 255]),
-            true,
-        );
-    image_builder
+pub fn setup_avatar_service() -> Result<AvatarService, crate::error::Error> {
+    let fira_sans_bold =
+        FontVec::try_from_vec(FIRA_SANS_BOLD_FILE.to_vec()).map_err(Error::from)?;
+
+    Ok(AvatarService::new(fira_sans_bold)?)
+}
+
+/// Returns the variables available in the text layers of welcome image templates.
+///
+/// These are the member variables plus `headline` and `subline`, the rendered image
+/// texts of the welcome settings.
+#[must_use]
+pub fn image_variables() -> Vec<&'static str> {
+    VARIABLES
+        .iter()
+        .copied()
+        .chain(["headline", "subline"])
+        .collect()
 }
 
-/// Downloads and processes a user's avatar image.
+/// Creates an `ImageBuilder` for generating welcome images.
 ///
 /// # Arguments
-/// * `img_url` - The URL of the user's avatar.
-/// * `temp_dir` - The temporary directory to store the image.
+/// * `image_context` - The context containing the banners, texts and template.
+/// * `avatar` - The user's avatar image.
+/// * `variables` - The values of the placeholders describing the member.
 ///
 /// # Returns
-/// Returns the path to the processed avatar image.
-///
-/// # Errors
-/// Returns an error if downloading or processing the image fails.
+/// Returns an `ImageBuilder` instance.
 #[fastrace::trace]
-async fn download_avatar(
-    img_url: &str,
-    temp_dir: &TempDir,
-) -> Result<std::path::PathBuf, Box<dyn std::error::Error + Sync + Send>> {
-    let image_bytes = reqwest::get(img_url).await?.bytes().await?;
-
-    let image_id = uuid::Uuid::new_v4();
-
-    let file_path = if img_url.to_lowercase().contains(".webp") {
-        temp_dir.path().join(format!("{image_id}.webp"))
-    } else {
-        temp_dir.path().join(format!("{image_id}.png"))
+fn create_image_builder(
+    image_context: &ImageContext,
+    avatar: ImageSource,
+    variables: &Variables,
+) -> ImageBuilder {
+    let assets = TemplateAssets {
+        background: ImageSource::from(&image_context.back_image),
+        foreground: ImageSource::from(&image_context.front_image),
+        avatar,
     };
 
-    let mut tmp_file = File::create(&file_path).await?;
-    tmp_file.write_all(&image_bytes).await?;
-    tmp_file.flush().await?;
+    let mut variables = variables.clone();
+    let headline = variables.render(&image_context.headline_message);
+    let subline = variables.render(&image_context.subline_message);
+    variables
+        .insert("headline", headline)
+        .insert("subline", subline);
 
-    drop(tmp_file);
-
-    let image = image::open(&file_path)?;
-    let image = image.resize(256, 256, FilterType::Nearest);
-    image.save(&file_path)?;
-
-    Ok(file_path)
+    image_context
+        .template
+        .build_with(&assets, |text| variables.render(text))
 }
 
 /// Handles a new member joining the guild.
 ///
-/// This function checks if the member is a bot, sends a suspicious user embed if necessary,
-/// and sends a welcome message if welcome settings are enabled.
+/// This function assigns the join roles of the guild, checks if the member is a bot, sends a
+/// suspicious user embed if necessary, and sends a welcome message if welcome settings are
+/// enabled. If welcome variants are active, one of them is picked for the welcome.
+///
+/// During a join flood, the member is welcomed in a batched welcome instead, see
+/// [`crate::join_burst::JoinBursts`].
 ///
 /// # Arguments
 /// * `ctx` - The Serenity context.
@@ -210,17 +307,28 @@ pub async fn handle_member_join(
         new_member.display_name()
     );
 
-    if new_member.user.bot {
-        warn!("Bot joined: '{}'.", new_member.display_name());
-        return Ok(());
-    }
-
     let db = &data.conn;
 
     let Some(guild) = guild::get_by_guild_id(db, new_member.guild_id.into()).await? else {
         return Ok(());
     };
 
+    // Members who still have to pass the membership screening get their roles later.
+    if !new_member.pending
+        && let Err(why) =
+            assign_join_roles(ctx, data, &guild, new_member.guild_id, &new_member.user).await
+    {
+        warn!(
+            "Could not assign the join roles of '{}': {why}",
+            new_member.display_name()
+        );
+    }
+
+    if new_member.user.bot {
+        warn!("Bot joined: '{}'.", new_member.display_name());
+        return Ok(());
+    }
+
     send_suspicious_user_embed(ctx, new_member, &guild).await?;
 
     if let Some(settings_id) = guild.welcome_settings_id {
@@ -228,22 +336,114 @@ pub async fn handle_member_join(
             return Ok(());
         };
 
-        if !welcome_settings.enabled {
+        if !welcome_settings.enabled && !welcome_settings.dm_enabled {
+            return Ok(());
+        }
+
+        let variants = active_variants(db, welcome_settings.id).await?;
+        let welcome_settings = match data.variants.pick(&welcome_settings, &variants) {
+            Some(variant) => {
+                info!(
+                    "Welcoming '{}' with the variant '{}'.",
+                    new_member.display_name(),
+                    variant.name
+                );
+                apply_variant(welcome_settings, variant)
+            }
+            None => welcome_settings,
+        };
+
+        let batched = data.join_bursts.admit(ctx, new_member, &welcome_settings);
+        if batched && !welcome_settings.dm_enabled {
             return Ok(());
         }
 
         if let Some(image_context) = ImageContext::init(db, &welcome_settings).await? {
-            send_welcome_message(ctx, data, image_context, new_member, &welcome_settings).await?;
+            send_welcome_message(
+                ctx,
+                data,
+                image_context,
+                new_member,
+                &welcome_settings,
+                batched,
+            )
+            .await?;
         }
     }
 
     Ok(())
 }
 
-/// Sends a welcome message to the specified channel.
+/// A welcome rendered for a member, ready to be sent.
+pub struct RenderedWelcome {
+    /// The text of the welcome message in the welcome channel.
+    pub chat_message: String,
+    /// The text of the welcome DM.
+    pub dm_message: String,
+    /// The welcome image, or `None` if it was not rendered.
+    pub attachment: Option<CreateAttachment>,
+}
+
+/// Renders the texts and welcome image of a member.
+///
+/// The welcome image is rendered on the render pool. If the pool is saturated, e.g.
+/// during a raid, or the render fails unexpectedly, the welcome has no image.
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `data` - The shared bot data.
+/// * `image_context` - The context for generating the welcome image.
+/// * `member` - The member to welcome.
+/// * `welcome_settings` - The welcome settings model.
+/// * `render_image` - Whether to render the welcome image.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if retrieving the guild or rendering fails.
+#[fastrace::trace]
+pub async fn render_welcome(
+    ctx: &serenity::Context,
+    data: &Data,
+    image_context: &ImageContext,
+    member: &serenity::Member,
+    welcome_settings: &entity::welcome_settings::Model,
+    render_image: bool,
+) -> Result<RenderedWelcome, PoiseError> {
+    let (guild_name, members) = guild_member_count(ctx, member.guild_id).await?;
+
+    let variables = member_variables(
+        &member.user,
+        member.display_name(),
+        member.joined_at,
+        &guild_name,
+        members,
+    );
+
+    let attachment = if render_image {
+        render_card(
+            data,
+            image_context,
+            member.guild_id,
+            &member.user,
+            Some(member),
+            &variables,
+        )
+        .await?
+    } else {
+        None
+    };
+
+    Ok(RenderedWelcome {
+        chat_message: variables.render(&welcome_settings.chat_message),
+        dm_message: variables.render(&welcome_settings.dm_message),
+        attachment,
+    })
+}
+
+/// Sends a welcome message to the specified channel and the welcome DM to the member.
 ///
-/// This function generates a welcome image and sends it along with a welcome message
-/// to the configured welcome channel.
+/// The welcome image is rendered once and attached to the channel message and, if
+/// enabled, to the DM. A batched member gets no channel message and no image, only the
+/// DM.
 ///
 /// # Arguments
 /// * `ctx` - The Serenity context.
@@ -251,9 +451,11 @@ pub async fn handle_member_join(
 /// * `image_context` - The context for generating the welcome image.
 /// * `new_member` - The new member who joined the guild.
 /// * `welcome_settings` - The welcome settings model.
+/// * `batched` - Whether the member is welcomed in a batched welcome.
 ///
 /// # Errors
-/// Returns a [`PoiseError`] if any operation fails.
+/// Returns a [`PoiseError`] if any operation fails. A DM which cannot be delivered is
+/// logged instead.
 #[fastrace::trace]
 async fn send_welcome_message(
     ctx: &serenity::Context,
@@ -261,47 +463,170 @@ async fn send_welcome_message(
     image_context: ImageContext,
     new_member: &serenity::Member,
     welcome_settings: &entity::welcome_settings::Model,
+    batched: bool,
 ) -> Result<(), PoiseError> {
-    let mut img_url = new_member
-        .avatar_url()
-        .or_else(|| new_member.user.avatar_url())
-        .unwrap_or_else(|| new_member.user.default_avatar_url());
+    let send_to_channel = welcome_settings.enabled && !batched;
+    let render_image = !batched
+        && (welcome_settings.enabled || (welcome_settings.dm_enabled && welcome_settings.dm_card));
+    let welcome = render_welcome(
+        ctx,
+        data,
+        &image_context,
+        new_member,
+        welcome_settings,
+        render_image,
+    )
+    .await?;
+
+    if send_to_channel {
+        let channel = ChannelId::new(welcome_settings.welcome_channel as u64);
+
+        let mut message = CreateMessage::new().content(welcome.chat_message);
+        if let Some(attachment) = &welcome.attachment {
+            message = message.add_file(attachment.clone());
+        }
 
-    if img_url.contains(".png") {
-        img_url = new_member.user.default_avatar_url();
+        channel.send_message(&ctx.http, message).await?;
     }
 
-    let partial_guild = ctx.http.get_guild(new_member.guild_id).await?;
-    let members = partial_guild.members(&ctx.http, None, None).await?.len();
-
-    let file_path = download_avatar(&img_url, &data.temp_dir).await?;
-    let image_builder = create_image_builder(
-        image_context.front_image,
-        image_context.back_image,
-        file_path,
-        image_context.headline_message,
-        image_context.subline_message,
-        IMAGE_POSITION,
-        new_member.display_name(),
-        members,
-        BIG_SCALE,
-        SMALL_SCALE,
-    );
+    if welcome_settings.dm_enabled {
+        let mut message = CreateMessage::new().content(welcome.dm_message);
+        if let Some(attachment) = welcome.attachment.filter(|_| welcome_settings.dm_card) {
+            message = message.add_file(attachment);
+        }
+
+        send_welcome_dm(ctx, new_member, message).await;
+    }
+
+    Ok(())
+}
 
-    let output_image = data.image_generator.generate(image_builder)?;
+/// Sends the welcome DM to a new member.
+///
+/// Members who do not accept direct messages from server members are skipped, and
+/// other failures are logged, so a DM never prevents the rest of the welcome.
+///
+/// # Arguments
+/// * `ctx` - The Serenity context.
+/// * `new_member` - The new member who joined the guild.
+/// * `message` - The welcome DM.
+#[fastrace::trace]
+async fn send_welcome_dm(
+    ctx: &serenity::Context,
+    new_member: &serenity::Member,
+    message: CreateMessage,
+) {
+    match new_member.user.direct_message(&ctx.http, message).await {
+        Ok(_) => {}
+        Err(serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response)))
+            if response.error.code == CANNOT_MESSAGE_USER =>
+        {
+            info!(
+                "'{}' does not accept direct messages, skipping the welcome DM.",
+                new_member.display_name()
+            );
+        }
+        Err(why) => warn!(
+            "Failed to send the welcome DM to '{}': {why}",
+            new_member.display_name()
+        ),
+    }
+}
+
+/// Renders a welcome or farewell card on the render pool.
+///
+/// # Arguments
+/// * `data` - The shared bot data.
+/// * `image_context` - The context for generating the card.
+/// * `guild_id` - The guild the card is rendered for.
+/// * `user` - The user shown on the card.
+/// * `member` - The member data of the user, if available.
+/// * `variables` - The values of the placeholders describing the member.
+///
+/// # Returns
+/// Returns the card, or `None` if the render pool is saturated or the render job
+/// panicked.
+///
+/// # Errors
+/// Returns a [`PoiseError`] if rendering fails.
+#[fastrace::trace]
+pub async fn render_card(
+    data: &Data,
+    image_context: &ImageContext,
+    guild_id: serenity::GuildId,
+    user: &serenity::User,
+    member: Option<&serenity::Member>,
+    variables: &Variables,
+) -> Result<Option<CreateAttachment>, PoiseError> {
+    let display_name = member.map_or_else(|| user.display_name(), serenity::Member::display_name);
+
+    // Skip the download if the render would be rejected anyway.
+    if data.render_pool.is_saturated() {
+        warn!("Render queue is full, sending the message for '{display_name}' without image.");
+        return Ok(None);
+    }
+
+    let avatar = data.avatars.get(user, member).await;
+    let image_builder = create_image_builder(image_context, avatar, variables);
+
+    let image_generator = data.image_generator.clone();
+    let Some(receiver) = data.render_pool.submit(guild_id.get(), move || {
+        render_attachment(&image_generator, image_builder)
+    }) else {
+        warn!("Render queue is full, sending the message for '{display_name}' without image.");
+        return Ok(None);
+    };
+
+    match receiver.await {
+        Ok(attachment) => Ok(Some(attachment?)),
+        Err(_) => {
+            warn!(
+                "Rendering the image for '{display_name}' failed, sending the message without image."
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Renders a welcome image and encodes it as attachment.
+///
+/// Animated avatars or banners produce an animated image. The frames are only decoded
+/// if one of the images is animated. If that fails, a still image is used.
+///
+/// # Arguments
+/// * `image_generator` - The image generator.
+/// * `image_builder` - The welcome image to render.
+///
+/// # Returns
+/// Returns the encoded image.
+///
+/// # Errors
+/// Returns an [`Error`] if rendering or encoding the still image fails.
+#[fastrace::trace]
+fn render_attachment(
+    image_generator: &ImageGenerator,
+    image_builder: ImageBuilder,
+) -> Result<CreateAttachment, Error> {
     let outfile_id = uuid::Uuid::new_v4();
-    let outfile_path = data.temp_dir.path().join(format!("{outfile_id}.png"));
-    output_image.save(&outfile_path)?;
+    let animation_options = AnimationOptions::default();
 
-    let channel = ChannelId::new(welcome_settings.welcome_channel as u64);
-    let message = welcome_settings
-        .chat_message
-        .replace("{user}", &format!("<@{}>", new_member.user.id))
-        .replace("{guild_name}", &partial_guild.name);
+    let animation = match image_generator.generate_animated(&image_builder, &animation_options) {
+        Ok(animation) => animation,
+        Err(why) => {
+            warn!("Could not render animated welcome image, using a still image: {why}");
+            None
+        }
+    };
+
+    if let Some(animation) = animation {
+        let file_name = format!("{outfile_id}.{}", animation_options.format.extension());
+        return Ok(CreateAttachment::bytes(animation, file_name));
+    }
 
-    let attachment = CreateAttachment::path(outfile_path).await?;
-    let message = CreateMessage::new().content(message).add_file(attachment);
+    let output_options = OutputOptions::default();
+    let output_image = image_generator.generate(image_builder)?;
+    let encoded = output::encode(&output_image, &output_options)?;
+    let file_name = format!("{outfile_id}.{}", output_options.format.extension());
 
-    channel.send_message(&ctx.http, message).await?;
-    Ok(())
+    Ok(CreateAttachment::bytes(encoded, file_name))
 }
@@ -0,0 +1,199 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+};
+
+use chrono::{Datelike, NaiveDate, Utc};
+use migration::{DbErr, sea_orm::DbConn};
+use rand::Rng;
+use welcome_service::welcome_variant;
+
+/// The variant mode picking a variant at random, weighted by the variant weights.
+pub const RANDOM_MODE: &str = "random";
+/// The variant mode picking the variants in turn, each as often as its weight.
+pub const ROUND_ROBIN_MODE: &str = "round_robin";
+
+/// Picks the welcome variant of a joining member.
+///
+/// Round-robin positions are kept per welcome settings in memory, so the rotation
+/// starts over when the bot restarts.
+#[derive(Debug, Default)]
+pub struct VariantPicker {
+    /// The next round-robin position of each welcome settings.
+    positions: Mutex<HashMap<i32, u64>>,
+}
+
+impl VariantPicker {
+    /// Picks one of the active variants using the variant mode of the welcome settings.
+    ///
+    /// # Arguments
+    /// * `welcome_settings` - The welcome settings model.
+    /// * `variants` - The variants which are active today.
+    ///
+    /// # Returns
+    /// Returns the picked variant, or `None` if no variant is active.
+    pub fn pick<'a>(
+        &self,
+        welcome_settings: &entity::welcome_settings::Model,
+        variants: &'a [entity::welcome_variant::Model],
+    ) -> Option<&'a entity::welcome_variant::Model> {
+        if welcome_settings.variant_mode != ROUND_ROBIN_MODE {
+            return pick_random(variants);
+        }
+
+        let position = {
+            let mut positions = self
+                .positions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let position = positions.entry(welcome_settings.id).or_default();
+            let current = *position;
+            *position = position.wrapping_add(1);
+            current
+        };
+
+        pick_at(variants, position % total_weight(variants).max(1))
+    }
+}
+
+/// Picks one of the variants at random, weighted by the variant weights.
+///
+/// # Arguments
+/// * `variants` - The variants to pick from.
+///
+/// # Returns
+/// Returns the picked variant, or `None` if there are no variants.
+#[must_use]
+pub fn pick_random(
+    variants: &[entity::welcome_variant::Model],
+) -> Option<&entity::welcome_variant::Model> {
+    if variants.is_empty() {
+        return None;
+    }
+
+    let position = rand::rng().random_range(0..total_weight(variants));
+    pick_at(variants, position)
+}
+
+/// Returns the variant covering a position when each variant covers as many positions
+/// as its weight.
+fn pick_at(
+    variants: &[entity::welcome_variant::Model],
+    mut position: u64,
+) -> Option<&entity::welcome_variant::Model> {
+    for variant in variants {
+        let weight = weight(variant);
+        if position < weight {
+            return Some(variant);
+        }
+        position -= weight;
+    }
+
+    None
+}
+
+/// Returns the sum of the variant weights.
+fn total_weight(variants: &[entity::welcome_variant::Model]) -> u64 {
+    variants.iter().map(weight).sum()
+}
+
+/// Returns the weight of a variant, at least 1.
+fn weight(variant: &entity::welcome_variant::Model) -> u64 {
+    variant.weight.max(1) as u64
+}
+
+/// Loads the variants of welcome settings which are active today.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `welcome_settings_id` - The ID of the welcome settings.
+///
+/// # Errors
+/// Returns a [`DbErr`] if the database operation fails.
+#[fastrace::trace]
+pub async fn active_variants(
+    db: &DbConn,
+    welcome_settings_id: i32,
+) -> Result<Vec<entity::welcome_variant::Model>, DbErr> {
+    let today = Utc::now().date_naive();
+
+    Ok(welcome_variant::get_all(db, welcome_settings_id)
+        .await?
+        .into_iter()
+        .filter(|variant| is_active(variant, today))
+        .collect())
+}
+
+/// Checks whether a variant is active on a day.
+///
+/// Variants without a date range are always active. The range repeats every year and
+/// includes its first and last day. A range ending before it starts, e.g. from `12-01`
+/// to `02-28`, wraps around the new year.
+///
+/// # Arguments
+/// * `variant` - The variant to check.
+/// * `today` - The day to check.
+#[must_use]
+pub fn is_active(variant: &entity::welcome_variant::Model, today: NaiveDate) -> bool {
+    let (Some(start), Some(end)) = (
+        variant.start_date.as_deref().and_then(parse_day),
+        variant.end_date.as_deref().and_then(parse_day),
+    ) else {
+        return true;
+    };
+
+    let day = (today.month(), today.day());
+    if start <= end {
+        start <= day && day <= end
+    } else {
+        start <= day || day <= end
+    }
+}
+
+/// Parses a day of the year in the format `MM-DD`, e.g. `10-31`.
+///
+/// # Returns
+/// Returns the month and day, or `None` if the text is not a valid day.
+#[must_use]
+pub fn parse_day(text: &str) -> Option<(u32, u32)> {
+    let (month, day) = text.split_once('-')?;
+    if month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+
+    // 2000 is a leap year, so 02-29 is accepted.
+    let date = NaiveDate::from_ymd_opt(2000, month.parse().ok()?, day.parse().ok()?)?;
+    Some((date.month(), date.day()))
+}
+
+/// Applies a variant to welcome settings.
+///
+/// The texts and banners the variant sets replace those of the welcome settings, the
+/// others are kept.
+///
+/// # Arguments
+/// * `welcome_settings` - The welcome settings model.
+/// * `variant` - The variant to apply.
+#[must_use]
+pub fn apply_variant(
+    mut welcome_settings: entity::welcome_settings::Model,
+    variant: &entity::welcome_variant::Model,
+) -> entity::welcome_settings::Model {
+    if let Some(chat_message) = &variant.chat_message {
+        welcome_settings.chat_message.clone_from(chat_message);
+    }
+    if let Some(image_headline) = &variant.image_headline {
+        welcome_settings.image_headline.clone_from(image_headline);
+    }
+    if let Some(image_subtext) = &variant.image_subtext {
+        welcome_settings.image_subtext.clone_from(image_subtext);
+    }
+    if let Some(back_banner) = variant.back_banner {
+        welcome_settings.back_banner = back_banner;
+    }
+    if let Some(front_banner) = variant.front_banner {
+        welcome_settings.front_banner = front_banner;
+    }
+
+    welcome_settings
+}
@@ -23,6 +23,7 @@ pub struct Model {
     pub ban_reason_template: Option<String>,
     #[sea_orm(column_type = "custom(\"enum_text\")")]
     pub punish_mode: String,
+    pub goodbye_settings_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -31,6 +32,8 @@ pub enum Relation {
     AutoBanRole,
     #[sea_orm(has_many = "super::ban_entry::Entity")]
     BanEntry,
+    #[sea_orm(has_many = "super::join_role::Entity")]
+    JoinRole,
     #[sea_orm(has_many = "super::kick_entry::Entity")]
     KickEntry,
 }
@@ -47,6 +50,12 @@ impl Related<super::ban_entry::Entity> for Entity {
     }
 }
 
+impl Related<super::join_role::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::JoinRole.def()
+    }
+}
+
 impl Related<super::kick_entry::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::KickEntry.def()
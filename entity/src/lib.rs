@@ -4,9 +4,12 @@ pub mod prelude;
 
 pub mod auto_ban_role;
 pub mod ban_entry;
+pub mod goodbye_settings;
 pub mod guild;
 pub mod image;
+pub mod join_role;
 pub mod kick_entry;
 pub mod twitch_broadcaster;
 pub mod web_user;
 pub mod welcome_settings;
+pub mod welcome_variant;
@@ -24,6 +24,18 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub modify_date: Option<String>,
     pub enabled: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub image_template: Option<String>,
+    pub dm_enabled: bool,
+    #[sea_orm(column_type = "Text")]
+    pub dm_message: String,
+    pub dm_card: bool,
+    #[sea_orm(column_type = "custom(\"enum_text\")")]
+    pub variant_mode: String,
+    pub burst_threshold: i32,
+    pub burst_interval: i32,
+    #[sea_orm(column_type = "Text")]
+    pub burst_message: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -44,6 +56,14 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Image1,
+    #[sea_orm(has_many = "super::welcome_variant::Entity")]
+    WelcomeVariant,
+}
+
+impl Related<super::welcome_variant::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WelcomeVariant.def()
+    }
 }
 
 impl ActiveModelBehavior for ActiveModel {}
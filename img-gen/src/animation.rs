@@ -0,0 +1,448 @@
+use std::{io::Cursor, path::Path};
+
+use image::{
+    codecs::{
+        gif::{GifDecoder, GifEncoder, Repeat},
+        webp::WebPDecoder,
+    },
+    AnimationDecoder, Delay, DynamicImage, Frame, ImageFormat, ImageReader,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// The shortest frame delay in milliseconds. Most viewers slow down faster GIFs anyway.
+const MIN_FRAME_DELAY: u32 = 20;
+
+/// The container format of an animated image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnimationFormat {
+    /// An animated GIF.
+    Gif,
+    /// An animated lossy WebP.
+    WebP,
+}
+
+impl AnimationFormat {
+    /// Returns the file extension of the format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Gif => "gif",
+            Self::WebP => "webp",
+        }
+    }
+}
+
+/// Options for rendering animated images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationOptions {
+    /// The container format of the output.
+    pub format: AnimationFormat,
+    /// The maximum number of frames in the output. Longer animations are sampled down.
+    pub max_frames: usize,
+    /// The maximum size of the encoded output in bytes.
+    ///
+    /// Frames are dropped until the output fits. If a single frame is still too large,
+    /// an [`Error::OutputTooLarge`] is returned.
+    pub max_bytes: usize,
+}
+
+impl Default for AnimationOptions {
+    /// Creates options for GIF output that stays below Discord's upload limit.
+    fn default() -> Self {
+        Self {
+            format: AnimationFormat::Gif,
+            max_frames: 50,
+            max_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// The decoded frames of a possibly animated image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameSequence {
+    /// The frames together with how long they are shown in milliseconds.
+    frames: Vec<(DynamicImage, u32)>,
+    /// The total duration of the animation in milliseconds.
+    duration: u32,
+}
+
+impl FrameSequence {
+    /// Loads every frame of an image file.
+    ///
+    /// Animated GIF and WebP files yield all of their frames, every other image
+    /// yields a single frame.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the image.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the file cannot be read or decoded.
+    pub(crate) fn load<T: AsRef<Path>>(path: T) -> Result<Self, Error> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    /// Decodes every frame of an encoded image.
+    ///
+    /// Animated GIF and WebP images yield all of their frames, every other image
+    /// yields a single frame.
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded image.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the image cannot be decoded.
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+
+        let frames = match reader.format() {
+            Some(ImageFormat::Gif) => GifDecoder::new(Cursor::new(bytes))?.into_frames(),
+            Some(ImageFormat::WebP) => {
+                let decoder = WebPDecoder::new(Cursor::new(bytes))?;
+
+                if !decoder.has_animation() {
+                    return Ok(Self::still(reader.decode()?));
+                }
+
+                decoder.into_frames()
+            }
+            _ => return Ok(Self::still(reader.decode()?)),
+        };
+
+        let frames = frames
+            .collect_frames()?
+            .into_iter()
+            .map(|frame| {
+                let (numerator, denominator) = frame.delay().numer_denom_ms();
+                let delay = (numerator / denominator.max(1)).max(MIN_FRAME_DELAY);
+
+                (DynamicImage::ImageRgba8(frame.into_buffer()), delay)
+            })
+            .collect::<Vec<_>>();
+
+        if frames.is_empty() {
+            return Ok(Self::still(reader.decode()?));
+        }
+
+        Ok(Self::new(frames))
+    }
+
+    /// Creates a sequence from frames and their delays.
+    fn new(frames: Vec<(DynamicImage, u32)>) -> Self {
+        let duration = frames.iter().map(|(_, delay)| delay).sum();
+
+        Self { frames, duration }
+    }
+
+    /// Creates a sequence consisting of a single image.
+    pub fn still(image: DynamicImage) -> Self {
+        Self::new(vec![(image, 0)])
+    }
+
+    /// Returns the first frame of the sequence.
+    pub fn first_frame(&self) -> &DynamicImage {
+        &self.frames[0].0
+    }
+
+    /// Checks whether the sequence has more than one frame.
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    /// Returns the size of the decoded frames in bytes.
+    pub fn byte_size(&self) -> usize {
+        self.frames
+            .iter()
+            .map(|(image, _)| image.as_bytes().len())
+            .sum()
+    }
+
+    /// Samples the sequence down to at most `max_frames` frames.
+    ///
+    /// Dropped frames extend the delay of the frame before them, so the animation keeps
+    /// its duration.
+    ///
+    /// # Arguments
+    /// * `max_frames` - The maximum number of frames kept.
+    #[must_use]
+    pub fn sample(self, max_frames: usize) -> Self {
+        if self.frames.len() <= max_frames.max(1) {
+            return self;
+        }
+
+        let step = self.frames.len().div_ceil(max_frames.max(1));
+        let frames = self
+            .frames
+            .chunks(step)
+            .map(|chunk| {
+                let delay = chunk.iter().map(|(_, delay)| delay).sum();
+                (chunk[0].0.clone(), delay)
+            })
+            .collect();
+
+        Self::new(frames)
+    }
+
+    /// Applies a function to every frame of the sequence.
+    ///
+    /// # Errors
+    /// Returns the first [`Error`] returned by `f`.
+    pub fn try_map(
+        &self,
+        mut f: impl FnMut(&DynamicImage) -> Result<DynamicImage, Error>,
+    ) -> Result<Self, Error> {
+        let frames = self
+            .frames
+            .iter()
+            .map(|(image, delay)| Ok((f(image)?, *delay)))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(Self::new(frames))
+    }
+
+    /// Returns the frame which is shown at the given time, looping the animation.
+    ///
+    /// # Arguments
+    /// * `time` - The time since the start of the animation in milliseconds.
+    pub(crate) fn frame_at(&self, time: u32) -> &DynamicImage {
+        if self.duration == 0 {
+            return &self.frames[0].0;
+        }
+
+        let mut time = time % self.duration;
+
+        for (image, delay) in &self.frames {
+            if time < *delay {
+                return image;
+            }
+
+            time -= delay;
+        }
+
+        &self.frames[self.frames.len() - 1].0
+    }
+
+    /// Returns the start time and delay of every frame, sampled down to at most `max_frames`.
+    ///
+    /// # Arguments
+    /// * `max_frames` - The maximum number of frames in the timeline.
+    pub(crate) fn timeline(&self, max_frames: usize) -> Vec<(u32, u32)> {
+        let step = self.frames.len().div_ceil(max_frames.max(1));
+        let mut timeline = Vec::with_capacity(self.frames.len() / step + 1);
+        let mut time = 0;
+
+        for chunk in self.frames.chunks(step) {
+            let delay = chunk.iter().map(|(_, delay)| delay).sum();
+            timeline.push((time, delay));
+            time += delay;
+        }
+
+        timeline
+    }
+
+    /// Returns the total duration of the animation in milliseconds.
+    pub(crate) const fn duration(&self) -> u32 {
+        self.duration
+    }
+}
+
+/// Checks whether an encoded image has more than one frame.
+///
+/// See [`frame_count`], the frames are counted without decoding them.
+///
+/// # Arguments
+/// * `bytes` - The encoded image.
+#[must_use]
+pub fn is_animated(bytes: &[u8]) -> bool {
+    frame_count(bytes) > 1
+}
+
+/// Counts the frames of an encoded image without decoding them.
+///
+/// Only the container of GIF and WebP images is read, so this is much cheaper than
+/// [`FrameSequence::decode`] and safe to call on untrusted uploads. Images of other
+/// formats have a single frame. Truncated files count the frames found before the end.
+///
+/// # Arguments
+/// * `bytes` - The encoded image.
+#[must_use]
+pub fn frame_count(bytes: &[u8]) -> usize {
+    match image::guess_format(bytes) {
+        Ok(ImageFormat::Gif) => gif_frame_count(bytes),
+        Ok(ImageFormat::WebP) => webp_frame_count(bytes),
+        _ => 1,
+    }
+}
+
+/// Counts the image descriptors of a GIF, skipping their compressed data.
+fn gif_frame_count(bytes: &[u8]) -> usize {
+    /// Returns the size of the color table announced by a packed field.
+    fn color_table_size(packed: u8) -> usize {
+        if packed & 0x80 == 0 {
+            0
+        } else {
+            3 << ((packed & 0x07) + 1)
+        }
+    }
+
+    /// Returns the position after the data sub-blocks starting at `position`.
+    fn skip_sub_blocks(bytes: &[u8], mut position: usize) -> usize {
+        while let Some(&size) = bytes.get(position) {
+            position += 1 + size as usize;
+            if size == 0 {
+                break;
+            }
+        }
+        position
+    }
+
+    // The header and logical screen descriptor, followed by the global color table.
+    let Some(&packed) = bytes.get(10) else {
+        return 0;
+    };
+    let mut position = 13 + color_table_size(packed);
+    let mut frames = 0;
+
+    while let Some(&block) = bytes.get(position) {
+        match block {
+            // An image descriptor, its local color table, the LZW code size and the data.
+            0x2c => {
+                let Some(&packed) = bytes.get(position + 9) else {
+                    break;
+                };
+                frames += 1;
+                position = skip_sub_blocks(bytes, position + 11 + color_table_size(packed));
+            }
+            // An extension, its label and its data.
+            0x21 => position = skip_sub_blocks(bytes, position + 2),
+            // The trailer, or a broken file.
+            _ => break,
+        }
+    }
+
+    frames
+}
+
+/// Counts the animation frames of a WebP, or returns `1` for still images.
+fn webp_frame_count(bytes: &[u8]) -> usize {
+    // The RIFF header is followed by chunks of a four character code, a little endian
+    // size and the data, padded to an even length.
+    let mut position = 12;
+    let mut frames = 0;
+
+    while let Some(header) = bytes.get(position..position + 8) {
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        if &header[..4] == b"ANMF" {
+            frames += 1;
+        }
+        position = position.saturating_add(8).saturating_add(size + size % 2);
+    }
+
+    frames.max(1)
+}
+
+/// Encodes rendered frames into an animated image that fits into the size limit.
+///
+/// Every other frame is dropped until the encoded output fits into `options.max_bytes`.
+///
+/// # Arguments
+/// * `frames` - The rendered frames together with their delays in milliseconds.
+/// * `options` - The output format and limits.
+///
+/// # Errors
+/// Returns an [`Error`] if encoding fails or a single frame does not fit into the limit.
+#[fastrace::trace]
+pub(crate) fn encode_within_budget(
+    mut frames: Vec<(DynamicImage, u32)>,
+    options: &AnimationOptions,
+) -> Result<Vec<u8>, Error> {
+    loop {
+        let encoded = encode(&frames, options.format)?;
+
+        if encoded.len() <= options.max_bytes {
+            return Ok(encoded);
+        }
+
+        if frames.len() <= 1 {
+            return Err(Error::OutputTooLarge(encoded.len()));
+        }
+
+        log::debug!(
+            "Animated image with {} frames is {} bytes, dropping frames.",
+            frames.len(),
+            encoded.len()
+        );
+
+        frames = frames
+            .chunks(2)
+            .map(|chunk| {
+                let delay = chunk.iter().map(|(_, delay)| delay).sum();
+                (chunk[0].0.clone(), delay)
+            })
+            .collect();
+    }
+}
+
+/// Encodes rendered frames into an animated image.
+///
+/// # Arguments
+/// * `frames` - The rendered frames together with their delays in milliseconds.
+/// * `format` - The container format of the output.
+///
+/// # Errors
+/// Returns an [`Error`] if encoding fails.
+fn encode(frames: &[(DynamicImage, u32)], format: AnimationFormat) -> Result<Vec<u8>, Error> {
+    match format {
+        AnimationFormat::Gif => {
+            let mut buffer = Vec::new();
+            let mut encoder = GifEncoder::new_with_speed(&mut buffer, 10);
+            encoder.set_repeat(Repeat::Infinite)?;
+            encoder.encode_frames(frames.iter().map(|(image, delay)| {
+                Frame::from_parts(
+                    image.to_rgba8(),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(*delay, 1),
+                )
+            }))?;
+            drop(encoder);
+
+            Ok(buffer)
+        }
+        AnimationFormat::WebP => {
+            let Some((first, _)) = frames.first() else {
+                return Ok(Vec::new());
+            };
+
+            let mut config = webp::WebPConfig::new()
+                .map_err(|()| Error::WebPError("Invalid WebP configuration.".to_string()))?;
+            config.quality = 80.;
+
+            let buffers = frames
+                .iter()
+                .map(|(image, _)| image.to_rgba8())
+                .collect::<Vec<_>>();
+            let mut encoder = webp::AnimEncoder::new(first.width(), first.height(), &config);
+            encoder.set_loop_count(0);
+
+            let mut timestamp = 0;
+            for (buffer, (_, delay)) in buffers.iter().zip(frames) {
+                encoder.add_frame(webp::AnimFrame::from_rgba(
+                    buffer,
+                    buffer.width(),
+                    buffer.height(),
+                    timestamp,
+                ));
+                timestamp += *delay as i32;
+            }
+
+            let encoded = encoder
+                .try_encode()
+                .map_err(|why| Error::WebPError(format!("{why:?}")))?;
+
+            Ok(encoded.to_vec())
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Renders a welcome card template to a file without running the Discord bot.
+//!
+//! ```sh
+//! cargo run -p img-gen --features cli --bin welcome-card -- \
+//!     --template discord-bot/assets/templates/welcome.toml \
+//!     --background discord-bot/assets/images/default_userbanner_back.png \
+//!     --foreground discord-bot/assets/images/default_userbanner.png \
+//!     --avatar avatar.png \
+//!     --font fsb=discord-bot/assets/FiraSans-Bold.ttf \
+//!     --font fmm=discord-bot/assets/FiraMono-Medium.ttf \
+//!     --name "Ferris" --members 1234 --watch
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use ab_glyph::FontVec;
+use clap::Parser;
+use img_gen::{
+    emoji::EmojiSet,
+    error::Error,
+    output::{self, OutputFormat, OutputOptions},
+    source::ImageSource,
+    template::{Template, TemplateAssets},
+    text_template::Variables,
+    ImageGenerator,
+};
+
+/// How often the watched files are checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+/// Sample values of the other placeholders the bot provides, so templates and texts
+/// written for the bot render without a `--set` for each of them.
+const SAMPLE_VALUES: [(&str, &str); 8] = [
+    ("username", "ferris"),
+    ("mention", "@Ferris"),
+    ("account_age", "3 years"),
+    ("account_age_days", "1096"),
+    ("guild", "Rustaceans"),
+    ("join_date", "October 17, 2026"),
+    ("user", "@Ferris"),
+    ("guild_name", "Rustaceans"),
+];
+
+/// Renders a welcome card template to a file.
+#[derive(Debug, Parser)]
+#[command(name = "welcome-card", version)]
+struct Args {
+    /// The TOML or JSON template to render.
+    #[arg(short, long)]
+    template: PathBuf,
+    /// The image used for the `background` source.
+    #[arg(long)]
+    background: PathBuf,
+    /// The image used for the `foreground` source.
+    #[arg(long)]
+    foreground: PathBuf,
+    /// The image used for the `avatar` source.
+    #[arg(long)]
+    avatar: PathBuf,
+    /// A font registered under a name, as `NAME=PATH`. Can be repeated.
+    #[arg(long = "font", value_name = "NAME=PATH", value_parser = parse_pair)]
+    fonts: Vec<(String, String)>,
+    /// A directory whose fonts are registered under their family and style name.
+    #[arg(long)]
+    fonts_dir: Option<PathBuf>,
+    /// A font used for characters missing from the template fonts. Can be repeated.
+    #[arg(long = "fallback-font", value_name = "PATH")]
+    fallback_fonts: Vec<PathBuf>,
+    /// A directory of Twemoji style PNGs used to draw emoji in color.
+    #[arg(long)]
+    emoji_dir: Option<PathBuf>,
+    /// The display name of the member.
+    #[arg(long, default_value = "Ferris")]
+    name: String,
+    /// The number of members in the guild.
+    #[arg(long, default_value_t = 1234)]
+    members: usize,
+    /// The image headline, a text template like in the bot, e.g. `{name|upper}`.
+    #[arg(long, default_value = "{name} just joined the server")]
+    headline: String,
+    /// The image subline, a text template like in the bot, e.g. `{members|ordinal}`.
+    #[arg(long, default_value = "You are the #{members} member")]
+    subline: String,
+    /// An additional placeholder value, as `KEY=VALUE`. Can be repeated.
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_pair)]
+    placeholders: Vec<(String, String)>,
+    /// The file the card is written to. The format follows the extension:
+    /// `png`, `webp` or `jpg`.
+    #[arg(short, long, default_value = "welcome.png")]
+    output: PathBuf,
+    /// Re-render whenever the template or one of the images changes.
+    #[arg(short, long)]
+    watch: bool,
+}
+
+impl Args {
+    /// Returns the values inserted for the placeholders of the template.
+    ///
+    /// The built-in placeholders match the ones the bot provides. The headline and
+    /// subline are rendered with the other values first, like the bot does.
+    fn variables(&self) -> Variables {
+        let mut variables = Variables::default();
+        for (name, value) in SAMPLE_VALUES {
+            variables.insert(name, value);
+        }
+        variables
+            .insert("name", self.name.clone())
+            .insert("members", self.members.to_string());
+        for (name, value) in &self.placeholders {
+            variables.insert(name, value.clone());
+        }
+
+        let headline = variables.render(&self.headline);
+        let subline = variables.render(&self.subline);
+        variables
+            .insert("headline", headline)
+            .insert("subline", subline);
+
+        variables
+    }
+
+    /// Returns the files whose changes trigger a new render in watch mode.
+    fn watched_files(&self) -> [&Path; 4] {
+        [
+            &self.template,
+            &self.background,
+            &self.foreground,
+            &self.avatar,
+        ]
+    }
+}
+
+/// Parses a `KEY=VALUE` argument.
+///
+/// # Errors
+/// Returns an error message if the argument contains no `=`.
+fn parse_pair(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got `{value}`"))
+}
+
+/// Loads a font file.
+///
+/// # Errors
+/// Returns an [`Error`] if the file cannot be read or is not a valid font.
+fn load_font(path: impl AsRef<Path>) -> Result<FontVec, Error> {
+    Ok(FontVec::try_from_vec(std::fs::read(path)?)?)
+}
+
+/// Sets up an image generator with the fonts and emoji given on the command line.
+///
+/// # Errors
+/// Returns an [`Error`] if a font or the fonts directory cannot be loaded.
+fn setup_image_generator(args: &Args) -> Result<ImageGenerator, Error> {
+    let mut generator = ImageGenerator::new();
+
+    for (name, path) in &args.fonts {
+        generator.add_font(name, load_font(path)?);
+    }
+
+    if let Some(fonts_dir) = &args.fonts_dir {
+        let names = generator.load_font_dir(fonts_dir)?;
+        eprintln!("Loaded fonts: {}", names.join(", "));
+    }
+
+    for path in &args.fallback_fonts {
+        generator.add_fallback_font(load_font(path)?);
+    }
+
+    if let Some(emoji_dir) = &args.emoji_dir {
+        generator.set_emoji(EmojiSet::new(emoji_dir));
+    }
+
+    Ok(generator)
+}
+
+/// Returns the output format matching the extension of the output file.
+fn output_format(path: &Path) -> OutputFormat {
+    match path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        Some("webp") => OutputFormat::WebPLossless,
+        Some("jpg" | "jpeg") => OutputFormat::Jpeg { quality: 90 },
+        _ => OutputFormat::Png,
+    }
+}
+
+/// Renders the template once and writes the card to the output file.
+///
+/// # Errors
+/// Returns an [`Error`] if the template is invalid, an image cannot be loaded or
+/// the card cannot be written.
+fn render(generator: &ImageGenerator, args: &Args) -> Result<(), Error> {
+    let template = Template::parse(&std::fs::read_to_string(&args.template)?)?;
+
+    let assets = TemplateAssets {
+        background: ImageSource::from(&args.background),
+        foreground: ImageSource::from(&args.foreground),
+        avatar: ImageSource::from(&args.avatar),
+    };
+
+    let image = generator.generate(template.build(&assets, &args.variables()))?;
+
+    let options = OutputOptions {
+        format: output_format(&args.output),
+        max_bytes: None,
+    };
+    std::fs::write(&args.output, output::encode(&image, &options)?)?;
+
+    Ok(())
+}
+
+/// Returns the latest modification time of the given files.
+///
+/// Missing files are skipped, so a file being replaced does not end the watch.
+fn last_modified(paths: &[&Path]) -> Option<SystemTime> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            std::fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .ok()
+        })
+        .max()
+}
+
+/// Renders the card once, or on every change of the watched files in watch mode.
+fn main() {
+    let args = Args::parse();
+
+    let generator = match setup_image_generator(&args) {
+        Ok(generator) => generator,
+        Err(why) => {
+            eprintln!("Failed to load fonts: {why}");
+            std::process::exit(1);
+        }
+    };
+
+    let render_once = || match render(&generator, &args) {
+        Ok(()) => {
+            eprintln!("Rendered {}.", args.output.display());
+            true
+        }
+        Err(why) => {
+            eprintln!("Failed to render {}: {why}", args.template.display());
+            false
+        }
+    };
+
+    if !args.watch {
+        if !render_once() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let watched_files = args.watched_files();
+    let mut rendered_at = last_modified(&watched_files);
+    render_once();
+    eprintln!("Watching for changes, press Ctrl+C to stop.");
+
+    loop {
+        thread::sleep(WATCH_INTERVAL);
+
+        let modified = last_modified(&watched_files);
+        if modified != rendered_at {
+            rendered_at = modified;
+            render_once();
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    hash::Hash,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::SystemTime,
+};
+
+use image::DynamicImage;
+
+use crate::{
+    animation::{self, FrameSequence},
+    error::Error,
+};
+
+/// The number of decoded files kept by default.
+pub const DEFAULT_CACHE_CAPACITY: usize = 16;
+
+/// A cached value together with the data used to evict it.
+struct CacheEntry<V> {
+    /// The cached value.
+    value: V,
+    /// The value of the access counter when the entry was last used.
+    last_used: u64,
+    /// The share of the capacity taken by the value.
+    weight: usize,
+}
+
+/// The entries of the cache, guarded by a single lock.
+struct CacheState<K, V> {
+    /// The cached values by key.
+    entries: HashMap<K, CacheEntry<V>>,
+    /// Incremented on every access to find the least recently used entry.
+    counter: u64,
+    /// The sum of the weights of the cached values.
+    weight: usize,
+}
+
+/// A thread-safe least recently used cache.
+///
+/// Once the cache is full, inserting a value evicts the value which was used least
+/// recently. Values are cloned out of the cache, so large values should be wrapped in
+/// an [`Arc`].
+///
+/// By default every value takes one unit of the capacity. Caches of values which differ
+/// a lot in size, e.g. decoded images, can weigh them with [`LruCache::with_weigher`].
+pub struct LruCache<K, V> {
+    /// The maximum total weight of the values kept.
+    capacity: usize,
+    /// Returns the share of the capacity taken by a value.
+    weigher: fn(&V) -> usize,
+    /// The cached entries.
+    state: Mutex<CacheState<K, V>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> LruCache<K, V> {
+    /// Creates a new, empty `LruCache`.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of values kept. `0` disables caching.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self::with_weigher(capacity, |_| 1)
+    }
+
+    /// Creates a new, empty `LruCache` limiting the total weight of its values.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum total weight of the values kept. `0` disables caching.
+    /// * `weigher` - Returns the weight of a value, e.g. its size in bytes.
+    #[must_use]
+    pub fn with_weigher(capacity: usize, weigher: fn(&V) -> usize) -> Self {
+        Self {
+            capacity,
+            weigher,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                counter: 0,
+                weight: 0,
+            }),
+        }
+    }
+
+    /// Returns a cached value and marks it as used.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the value.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let mut state = self.lock();
+        state.counter += 1;
+        let counter = state.counter;
+
+        state.entries.get_mut(key).map(|entry| {
+            entry.last_used = counter;
+            entry.value.clone()
+        })
+    }
+
+    /// Caches a value, evicting the least recently used values if the cache is full.
+    ///
+    /// Values weighing more than the whole capacity are not cached.
+    ///
+    /// # Arguments
+    /// * `key` - The key of the value.
+    /// * `value` - The value to cache.
+    pub fn insert(&self, key: K, value: V) {
+        let weight = (self.weigher)(&value);
+        if weight > self.capacity || self.capacity == 0 {
+            return;
+        }
+
+        let mut state = self.lock();
+        state.counter += 1;
+        let last_used = state.counter;
+
+        let entry = CacheEntry {
+            value,
+            last_used,
+            weight,
+        };
+        if let Some(replaced) = state.entries.insert(key, entry) {
+            state.weight -= replaced.weight;
+        }
+        state.weight += weight;
+
+        while state.weight > self.capacity {
+            let Some(oldest) = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+
+            if let Some(evicted) = state.entries.remove(&oldest) {
+                state.weight -= evicted.weight;
+            }
+        }
+    }
+
+    /// Keeps only the values for which `f` returns `true`.
+    ///
+    /// # Arguments
+    /// * `f` - Decides whether to keep the value of a key.
+    pub fn retain(&self, mut f: impl FnMut(&K, &V) -> bool) {
+        let mut state = self.lock();
+        state.entries.retain(|key, entry| f(key, &entry.value));
+        state.weight = state.entries.values().map(|entry| entry.weight).sum();
+    }
+
+    /// Removes every cached value.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.weight = 0;
+    }
+
+    /// Locks the cache, ignoring panics of other threads holding the lock.
+    fn lock(&self) -> MutexGuard<'_, CacheState<K, V>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A least recently used cache of decoded image files.
+///
+/// Entries are validated against the modification time of the file, so replaced
+/// banners are picked up without restarting.
+pub(crate) struct AssetCache {
+    /// The decoded frames and the modification time of the file when it was decoded.
+    entries: LruCache<PathBuf, (SystemTime, Arc<FrameSequence>)>,
+}
+
+impl AssetCache {
+    /// Creates a new, empty `AssetCache`.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of decoded files kept. `0` disables caching.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: LruCache::new(capacity),
+        }
+    }
+
+    /// Returns the decoded frames of a file, decoding it if it is not cached or has changed.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the image file.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the file cannot be read or decoded.
+    pub(crate) fn get(&self, path: &Path) -> Result<Arc<FrameSequence>, Error> {
+        let modified = std::fs::metadata(path)?.modified()?;
+
+        if let Some(frames) = self.cached(path, modified) {
+            return Ok(frames);
+        }
+
+        // Decode without holding the lock, so other renders are not blocked.
+        let frames = Arc::new(FrameSequence::load(path)?);
+        self.entries
+            .insert(path.to_path_buf(), (modified, frames.clone()));
+
+        Ok(frames)
+    }
+
+    /// Returns the first frame of a file.
+    ///
+    /// Animated files which are not cached yet are not decoded completely, only their
+    /// first frame is. Other files are decoded and cached like with [`Self::get`].
+    ///
+    /// # Arguments
+    /// * `path` - The path to the image file.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the file cannot be read or decoded.
+    pub(crate) fn first_frame(&self, path: &Path) -> Result<DynamicImage, Error> {
+        let modified = std::fs::metadata(path)?.modified()?;
+
+        if let Some(frames) = self.cached(path, modified) {
+            return Ok(frames.first_frame().clone());
+        }
+
+        let bytes = std::fs::read(path)?;
+        if animation::is_animated(&bytes) {
+            return Ok(image::load_from_memory(&bytes)?);
+        }
+
+        let frames = Arc::new(FrameSequence::decode(&bytes)?);
+        self.entries
+            .insert(path.to_path_buf(), (modified, frames.clone()));
+
+        Ok(frames.first_frame().clone())
+    }
+
+    /// Checks whether a file has more than one frame, without decoding it completely.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the image file.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the file cannot be read or decoded.
+    pub(crate) fn is_animated(&self, path: &Path) -> Result<bool, Error> {
+        let modified = std::fs::metadata(path)?.modified()?;
+
+        match self.cached(path, modified) {
+            Some(frames) => Ok(frames.is_animated()),
+            None => Ok(animation::is_animated(&std::fs::read(path)?)),
+        }
+    }
+
+    /// Returns the cached frames of a file if the file has not changed since.
+    fn cached(&self, path: &Path, modified: SystemTime) -> Option<Arc<FrameSequence>> {
+        self.entries
+            .get(path)
+            .filter(|(cached_modified, _)| *cached_modified == modified)
+            .map(|(_, frames)| frames)
+    }
+
+    /// Removes every cached file.
+    pub(crate) fn clear(&self) {
+        self.entries.clear();
+    }
+}
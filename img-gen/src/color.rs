@@ -0,0 +1,66 @@
+use std::fmt;
+
+use image::Rgba;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// An RGBA color which is written as a hex string (`#rrggbb` or `#rrggbbaa`) when serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color(pub Rgba<u8>);
+
+impl Color {
+    /// Parses a color from a hex string.
+    ///
+    /// # Arguments
+    /// * `value` - The hex string, with or without a leading `#`.
+    ///
+    /// # Returns
+    /// Returns `None` if the string is not a valid 6 or 8 digit hex color.
+    #[must_use]
+    pub fn from_hex(value: &str) -> Option<Self> {
+        let hex = value.strip_prefix('#').unwrap_or(value);
+
+        if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+            return None;
+        }
+
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+        let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+
+        Some(Self(Rgba([channel(0)?, channel(2)?, channel(4)?, alpha])))
+    }
+}
+
+impl fmt::Display for Color {
+    /// Formats the color as an 8 digit hex string.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b, a] = self.0 .0;
+        write!(f, "#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+impl From<Rgba<u8>> for Color {
+    fn from(value: Rgba<u8>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Color> for Rgba<u8> {
+    fn from(value: Color) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+
+        Self::from_hex(&value)
+            .ok_or_else(|| de::Error::custom(format!("invalid hex color '{value}'")))
+    }
+}
@@ -0,0 +1,194 @@
+use image::{imageops, DynamicImage, GenericImage, GenericImageView, GrayImage, Luma, Pixel};
+use imageproc::{
+    filter::gaussian_blur_f32,
+    morphology::{grayscale_dilate, Mask},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::color::Color;
+
+/// An outline drawn around every glyph.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Stroke {
+    /// The width of the outline in pixels.
+    pub width: u8,
+    /// The color of the outline.
+    pub color: Color,
+}
+
+/// A blurred copy of the text drawn behind it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Shadow {
+    /// The horizontal offset of the shadow in pixels.
+    #[serde(default)]
+    pub offset_x: i32,
+    /// The vertical offset of the shadow in pixels.
+    #[serde(default)]
+    pub offset_y: i32,
+    /// The blur radius of the shadow in pixels. `0` draws a hard shadow.
+    #[serde(default)]
+    pub blur_radius: f32,
+    /// The color of the shadow.
+    pub color: Color,
+}
+
+/// A soft halo around the text.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Glow {
+    /// How far the glow reaches beyond the glyphs in pixels.
+    pub radius: u8,
+    /// The color of the glow.
+    pub color: Color,
+}
+
+/// Effects which keep text readable on busy backgrounds.
+///
+/// The effects are drawn behind the text in the order glow, shadow, stroke.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextEffects {
+    /// An optional outline around the glyphs.
+    #[serde(default)]
+    pub stroke: Option<Stroke>,
+    /// An optional drop shadow.
+    #[serde(default)]
+    pub shadow: Option<Shadow>,
+    /// An optional outer glow.
+    #[serde(default)]
+    pub glow: Option<Glow>,
+}
+
+impl TextEffects {
+    /// Checks whether no effect is enabled.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.stroke.is_none() && self.shadow.is_none() && self.glow.is_none()
+    }
+
+    /// Draws the effects of a text onto an image.
+    ///
+    /// # Arguments
+    /// * `base_image` - The image to draw onto.
+    /// * `draw_coverage` - Draws the text in white onto a black canvas of the size of
+    ///   `base_image`. The result is used as the shape of the text.
+    #[fastrace::trace]
+    pub(crate) fn apply(
+        &self,
+        base_image: &mut DynamicImage,
+        draw_coverage: impl Fn(&mut GrayImage),
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        let mut coverage = GrayImage::new(base_image.width(), base_image.height());
+        draw_coverage(&mut coverage);
+
+        let Some((x, y, width, height)) = self.region(&coverage) else {
+            return;
+        };
+
+        let coverage = imageops::crop_imm(&coverage, x, y, width, height).to_image();
+
+        if let Some(glow) = &self.glow {
+            let mut halo = grayscale_dilate(&coverage, &Mask::disk(glow.radius / 2));
+            if glow.radius > 0 {
+                halo = gaussian_blur_f32(&halo, f32::from(glow.radius) / 2.);
+            }
+
+            composite(base_image, &halo, x, y, glow.color);
+        }
+
+        if let Some(shadow) = &self.shadow {
+            let mut shape = GrayImage::new(width, height);
+            imageops::overlay(
+                &mut shape,
+                &coverage,
+                i64::from(shadow.offset_x),
+                i64::from(shadow.offset_y),
+            );
+
+            if shadow.blur_radius > 0. {
+                shape = gaussian_blur_f32(&shape, shadow.blur_radius / 2.);
+            }
+
+            composite(base_image, &shape, x, y, shadow.color);
+        }
+
+        if let Some(stroke) = &self.stroke {
+            let outline = grayscale_dilate(&coverage, &Mask::disk(stroke.width));
+            composite(base_image, &outline, x, y, stroke.color);
+        }
+    }
+
+    /// Returns the area around the text that the effects can reach.
+    ///
+    /// # Returns
+    /// Returns `x`, `y`, `width` and `height` of the area, or `None` if nothing was drawn.
+    fn region(&self, coverage: &GrayImage) -> Option<(u32, u32, u32, u32)> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+
+        for (x, y, pixel) in coverage.enumerate_pixels() {
+            if pixel[0] > 0 {
+                bounds = Some(match bounds {
+                    Some((min_x, min_y, max_x, max_y)) => {
+                        (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+                    }
+                    None => (x, y, x, y),
+                });
+            }
+        }
+
+        let (min_x, min_y, max_x, max_y) = bounds?;
+        let margin = self.margin();
+
+        let left = min_x.saturating_sub(margin);
+        let top = min_y.saturating_sub(margin);
+        let right = (max_x + margin + 1).min(coverage.width());
+        let bottom = (max_y + margin + 1).min(coverage.height());
+
+        Some((left, top, right - left, bottom - top))
+    }
+
+    /// Returns how many pixels the effects reach beyond the glyphs.
+    fn margin(&self) -> u32 {
+        let stroke = self.stroke.map_or(0, |stroke| u32::from(stroke.width));
+        let glow = self.glow.map_or(0, |glow| u32::from(glow.radius) * 2);
+        let shadow = self.shadow.map_or(0, |shadow| {
+            shadow
+                .offset_x
+                .unsigned_abs()
+                .max(shadow.offset_y.unsigned_abs())
+                + (shadow.blur_radius * 1.5).ceil() as u32
+        });
+
+        stroke.max(glow).max(shadow) + 1
+    }
+}
+
+/// Blends a color onto an image, using a grayscale image as the alpha mask.
+///
+/// # Arguments
+/// * `base_image` - The image to draw onto.
+/// * `mask` - The alpha mask.
+/// * `x` - The x-coordinate of the mask on the image.
+/// * `y` - The y-coordinate of the mask on the image.
+/// * `color` - The color to draw.
+fn composite(base_image: &mut DynamicImage, mask: &GrayImage, x: u32, y: u32, color: Color) {
+    for (mask_x, mask_y, Luma([coverage])) in mask.enumerate_pixels() {
+        if *coverage == 0 {
+            continue;
+        }
+
+        let (target_x, target_y) = (x + mask_x, y + mask_y);
+        if !base_image.in_bounds(target_x, target_y) {
+            continue;
+        }
+
+        let mut source = color.0;
+        source[3] = (u16::from(source[3]) * u16::from(*coverage) / 255) as u8;
+
+        let mut pixel = base_image.get_pixel(target_x, target_y);
+        pixel.blend(&source);
+        base_image.put_pixel(target_x, target_y, pixel);
+    }
+}
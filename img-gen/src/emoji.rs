@@ -0,0 +1,154 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use image::RgbaImage;
+
+use crate::cache::LruCache;
+
+/// The number of emoji images kept in memory, including emoji without an image.
+const EMOJI_CACHE_CAPACITY: usize = 512;
+
+/// The zero width joiner which combines several emoji into one.
+const ZERO_WIDTH_JOINER: char = '\u{200d}';
+/// The variation selector which requests the emoji presentation of a character.
+const EMOJI_PRESENTATION: char = '\u{fe0f}';
+/// The variation selector which requests the text presentation of a character.
+const TEXT_PRESENTATION: char = '\u{fe0e}';
+/// The combining character which turns a digit, `#` or `*` into a keycap.
+const COMBINING_KEYCAP: char = '\u{20e3}';
+
+/// A directory of color emoji images, e.g. the 72x72 PNGs of Twemoji.
+///
+/// Every file is named after the code points of the emoji in lowercase hexadecimal,
+/// joined by `-`, such as `1f44b.png` or `1f468-200d-1f4bb.png`. The emoji presentation
+/// selector `fe0f` may be left out of the file name for sequences without a joiner.
+pub struct EmojiSet {
+    /// The directory containing the emoji images.
+    directory: PathBuf,
+    /// The recently used images by file name. `None` marks emoji without an image.
+    cache: LruCache<String, Option<Arc<RgbaImage>>>,
+}
+
+impl EmojiSet {
+    /// Creates a new `EmojiSet` reading images from a directory.
+    ///
+    /// Images are loaded lazily the first time an emoji is drawn, and the most recently
+    /// used ones are kept in memory.
+    ///
+    /// # Arguments
+    /// * `directory` - The directory containing the emoji images.
+    pub fn new<T: AsRef<Path>>(directory: T) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            cache: LruCache::new(EMOJI_CACHE_CAPACITY),
+        }
+    }
+
+    /// Returns the image of an emoji grapheme cluster.
+    ///
+    /// # Arguments
+    /// * `grapheme` - A single grapheme cluster.
+    ///
+    /// # Returns
+    /// Returns the image, or `None` if the grapheme is not an emoji or no image exists for it.
+    pub(crate) fn get(&self, grapheme: &str) -> Option<Arc<RgbaImage>> {
+        if !is_emoji(grapheme) {
+            return None;
+        }
+
+        let name = file_name(grapheme);
+
+        if let Some(image) = self.cache.get(&name) {
+            return image;
+        }
+
+        let image = self.load(&name).or_else(|| {
+            let stripped = file_name(&grapheme.replace(EMOJI_PRESENTATION, ""));
+            (stripped != name).then(|| self.load(&stripped)).flatten()
+        });
+
+        self.cache.insert(name, image.clone());
+        image
+    }
+
+    /// Loads the image with the given file name from the directory.
+    fn load(&self, name: &str) -> Option<Arc<RgbaImage>> {
+        let path = self.directory.join(format!("{name}.png"));
+
+        if !path.is_file() {
+            return None;
+        }
+
+        match image::open(&path) {
+            Ok(image) => Some(Arc::new(image.to_rgba8())),
+            Err(why) => {
+                log::warn!("Failed to load emoji image {}: {why}", path.display());
+                None
+            }
+        }
+    }
+}
+
+/// Returns the file name of an emoji image without extension.
+fn file_name(grapheme: &str) -> String {
+    grapheme
+        .chars()
+        .map(|c| format!("{:x}", u32::from(c)))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Checks whether a grapheme cluster is drawn as an emoji.
+///
+/// Graphemes are emoji if they contain a character which is shown as emoji by default,
+/// or if they request the emoji presentation, form a keycap or join several emoji.
+/// Characters like `©` or `♥` without a variation selector stay text, as do graphemes
+/// requesting the text presentation.
+fn is_emoji(grapheme: &str) -> bool {
+    if grapheme.contains(TEXT_PRESENTATION) {
+        return false;
+    }
+
+    grapheme.chars().any(|c| {
+        matches!(
+            c,
+            ZERO_WIDTH_JOINER | EMOJI_PRESENTATION | COMBINING_KEYCAP
+                | '\u{1f000}'..='\u{1faff}'
+                | '\u{231a}'..='\u{231b}'
+                | '\u{23e9}'..='\u{23ec}'
+                | '\u{23f0}'
+                | '\u{23f3}'
+                | '\u{25fd}'..='\u{25fe}'
+                | '\u{2614}'..='\u{2615}'
+                | '\u{2648}'..='\u{2653}'
+                | '\u{267f}'
+                | '\u{2693}'
+                | '\u{26a1}'
+                | '\u{26aa}'..='\u{26ab}'
+                | '\u{26bd}'..='\u{26be}'
+                | '\u{26c4}'..='\u{26c5}'
+                | '\u{26ce}'
+                | '\u{26d4}'
+                | '\u{26ea}'
+                | '\u{26f2}'..='\u{26f3}'
+                | '\u{26f5}'
+                | '\u{26fa}'
+                | '\u{26fd}'
+                | '\u{2705}'
+                | '\u{270a}'..='\u{270b}'
+                | '\u{2728}'
+                | '\u{274c}'
+                | '\u{274e}'
+                | '\u{2753}'..='\u{2755}'
+                | '\u{2757}'
+                | '\u{2795}'..='\u{2797}'
+                | '\u{27b0}'
+                | '\u{27bf}'
+                | '\u{2b1b}'..='\u{2b1c}'
+                | '\u{2b50}'
+                | '\u{2b55}'
+        )
+    })
+}
@@ -20,6 +20,31 @@ pub enum Error {
     ///
     /// This error is typically caused by issues with the `ab_glyph` crate.
     InvalidFont(InvalidFont),
+
+    /// Error that occurs when a TOML template cannot be parsed.
+    ///
+    /// This error is typically caused by issues with the `toml` crate.
+    TomlError(toml::de::Error),
+
+    /// Error that occurs when a JSON template cannot be parsed.
+    ///
+    /// This error is typically caused by issues with the `serde_json` crate.
+    JsonError(serde_json::Error),
+
+    /// Error that occurs when an image cannot be encoded as WebP.
+    ///
+    /// # Fields
+    /// - `String`: The reason reported by the WebP encoder.
+    WebPError(String),
+
+    /// Error indicating that the encoded image does not fit into the configured size limit.
+    ///
+    /// # Fields
+    /// - `usize`: The size of the smallest encoding in bytes.
+    OutputTooLarge(usize),
+
+    /// Error that occurs while reading an image file.
+    IoError(std::io::Error),
 }
 
 impl fmt::Display for Error {
@@ -35,6 +60,13 @@ impl fmt::Display for Error {
             Self::ImageError(why) => write!(f, "{why}"),
             Self::FontNotFound(why) => write!(f, "{why}"),
             Self::InvalidFont(why) => write!(f, "{why}"),
+            Self::TomlError(why) => write!(f, "{why}"),
+            Self::JsonError(why) => write!(f, "{why}"),
+            Self::WebPError(why) => write!(f, "{why}"),
+            Self::OutputTooLarge(size) => {
+                write!(f, "The encoded image is too large ({size} bytes).")
+            }
+            Self::IoError(why) => write!(f, "{why}"),
         }
     }
 }
@@ -66,3 +98,42 @@ impl From<InvalidFont> for Error {
         Self::InvalidFont(value)
     }
 }
+
+impl From<toml::de::Error> for Error {
+    /// Converts a `toml::de::Error` into an `Error`.
+    ///
+    /// # Arguments
+    /// * `value` - The TOML error to convert.
+    ///
+    /// # Returns
+    /// Returns an `Error::TomlError` variant.
+    fn from(value: toml::de::Error) -> Self {
+        Self::TomlError(value)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    /// Converts a `serde_json::Error` into an `Error`.
+    ///
+    /// # Arguments
+    /// * `value` - The JSON error to convert.
+    ///
+    /// # Returns
+    /// Returns an `Error::JsonError` variant.
+    fn from(value: serde_json::Error) -> Self {
+        Self::JsonError(value)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    /// Converts a `std::io::Error` into an `Error`.
+    ///
+    /// # Arguments
+    /// * `value` - The IO error to convert.
+    ///
+    /// # Returns
+    /// Returns an `Error::IoError` variant.
+    fn from(value: std::io::Error) -> Self {
+        Self::IoError(value)
+    }
+}
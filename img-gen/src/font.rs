@@ -0,0 +1,348 @@
+use std::sync::Arc;
+
+use ab_glyph::{Font, FontVec, GlyphId, PxScale, ScaleFont};
+use image::{
+    imageops::{self, FilterType},
+    DynamicImage, GrayImage, Luma, Rgba, RgbaImage,
+};
+use imageproc::drawing::draw_text_mut;
+use ttf_parser::name_id;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::emoji::EmojiSet;
+
+/// An ordered list of fonts registered under one name.
+///
+/// Every character is drawn with the first font of the family that has a glyph for it,
+/// so a family can combine a Latin display font with fonts for other scripts.
+pub struct FontFamily {
+    /// The fonts of the family, the primary font first.
+    fonts: Vec<FontVec>,
+}
+
+impl FontFamily {
+    /// Creates a new `FontFamily` consisting of a single font.
+    ///
+    /// # Arguments
+    /// * `font` - The primary font, which also provides the line metrics.
+    #[must_use]
+    pub fn new(font: FontVec) -> Self {
+        Self { fonts: vec![font] }
+    }
+
+    /// Appends a fallback font to the family.
+    ///
+    /// # Arguments
+    /// * `font` - The font used for characters the previous fonts do not support.
+    #[must_use]
+    pub fn with_fallback(mut self, font: FontVec) -> Self {
+        self.fonts.push(font);
+
+        self
+    }
+
+    /// Returns the fonts of the family, the primary font first.
+    pub(crate) fn fonts(&self) -> impl Iterator<Item = &FontVec> {
+        self.fonts.iter()
+    }
+}
+
+impl From<FontVec> for FontFamily {
+    /// Creates a `FontFamily` consisting of a single font.
+    fn from(font: FontVec) -> Self {
+        Self::new(font)
+    }
+}
+
+/// Reads the name a font is registered under from its `name` table.
+///
+/// The name combines the family and style, e.g. `Fira Sans Bold`. Regular styles, which
+/// some fonts call `Book`, `Normal` or `Roman`, only use the family name. English names
+/// are preferred.
+///
+/// # Arguments
+/// * `data` - The contents of the font file.
+///
+/// # Returns
+/// Returns the name, or `None` if the font has no readable family name.
+pub(crate) fn font_name(data: &[u8]) -> Option<String> {
+    let face = ttf_parser::Face::parse(data, 0).ok()?;
+
+    let find = |ids: &[u16]| {
+        ids.iter().find_map(|id| {
+            let names = face
+                .names()
+                .into_iter()
+                .filter(|name| name.name_id == *id)
+                .collect::<Vec<_>>();
+
+            names
+                .iter()
+                .filter(|name| name.language() == ttf_parser::Language::English_UnitedStates)
+                .chain(&names)
+                .find_map(ttf_parser::name::Name::to_string)
+        })
+    };
+
+    let family = find(&[name_id::TYPOGRAPHIC_FAMILY, name_id::FAMILY])?;
+    let style = find(&[name_id::TYPOGRAPHIC_SUBFAMILY, name_id::SUBFAMILY]);
+
+    match style.as_deref().map(str::trim) {
+        None | Some("" | "Regular" | "Book" | "Normal" | "Roman") => {
+            Some(family.trim().to_string())
+        }
+        Some(style) => Some(format!("{} {style}", family.trim())),
+    }
+}
+
+/// A part of a text which is drawn in one piece.
+enum Run<'a, 't> {
+    /// Characters drawn with the same font.
+    Text(&'a FontVec, &'t str),
+    /// A color emoji drawn from an image.
+    Emoji(Arc<RgbaImage>),
+}
+
+/// The fonts used to draw a single text element.
+///
+/// It consists of the fonts of a [`FontFamily`] followed by the fallback fonts
+/// of the generator, and optionally the color emoji drawn inline with the text.
+pub(crate) struct FontStack<'a> {
+    /// The fonts in the order they are tried.
+    fonts: Vec<&'a FontVec>,
+    /// The emoji images drawn instead of emoji characters.
+    emoji: Option<&'a EmojiSet>,
+}
+
+impl<'a> FontStack<'a> {
+    /// Creates a new `FontStack` from a family and the generator wide fallback fonts.
+    ///
+    /// # Arguments
+    /// * `family` - The family of the text element.
+    /// * `fallback_fonts` - The fonts tried after every font of the family.
+    /// * `emoji` - The emoji images, or `None` to draw emoji with the fonts.
+    pub(crate) fn new(
+        family: &'a FontFamily,
+        fallback_fonts: &'a [FontVec],
+        emoji: Option<&'a EmojiSet>,
+    ) -> Self {
+        Self {
+            fonts: family.fonts().chain(fallback_fonts).collect(),
+            emoji,
+        }
+    }
+
+    /// Returns the primary font, which provides the line metrics.
+    pub(crate) fn primary(&self) -> &'a FontVec {
+        self.fonts[0]
+    }
+
+    /// Returns the distance between the tops of two lines.
+    pub(crate) fn line_height(&self, scale: PxScale) -> f32 {
+        let font = self.primary().as_scaled(scale);
+        font.height() + font.line_gap()
+    }
+
+    /// Returns the width of a text in pixels.
+    pub(crate) fn text_width(&self, scale: PxScale, text: &str) -> u32 {
+        self.runs(text)
+            .iter()
+            .map(|run| self.advance(run, scale))
+            .sum::<f32>() as u32
+    }
+
+    /// Draws text, picking a font for every character and drawing emoji from images.
+    ///
+    /// # Arguments
+    /// * `image` - The image to draw onto.
+    /// * `color` - The color of the text.
+    /// * `x` - The x-coordinate of the text position.
+    /// * `y` - The y-coordinate of the text position.
+    /// * `scale` - The scale of the text.
+    /// * `text` - The text to draw.
+    pub(crate) fn draw(
+        &self,
+        image: &mut DynamicImage,
+        color: Rgba<u8>,
+        x: i32,
+        y: i32,
+        scale: PxScale,
+        text: &str,
+    ) {
+        for (run, run_x, run_y) in self.place(x, y, scale, text) {
+            match run {
+                Run::Text(font, run) => draw_text_mut(image, color, run_x, run_y, scale, font, run),
+                Run::Emoji(emoji) => {
+                    let size = self.emoji_size(scale);
+                    let emoji = imageops::resize(&*emoji, size, size, FilterType::Triangle);
+                    imageops::overlay(image, &emoji, i64::from(run_x), i64::from(run_y));
+                }
+            }
+        }
+    }
+
+    /// Draws the shape of a text in white onto a grayscale image.
+    ///
+    /// Emoji are drawn as their alpha channel.
+    ///
+    /// # Arguments
+    /// * `coverage` - The image to draw onto.
+    /// * `x` - The x-coordinate of the text position.
+    /// * `y` - The y-coordinate of the text position.
+    /// * `scale` - The scale of the text.
+    /// * `text` - The text to draw.
+    pub(crate) fn draw_coverage(
+        &self,
+        coverage: &mut GrayImage,
+        x: i32,
+        y: i32,
+        scale: PxScale,
+        text: &str,
+    ) {
+        for (run, run_x, run_y) in self.place(x, y, scale, text) {
+            match run {
+                Run::Text(font, run) => {
+                    draw_text_mut(coverage, Luma([255]), run_x, run_y, scale, font, run);
+                }
+                Run::Emoji(emoji) => {
+                    let size = self.emoji_size(scale);
+                    let emoji = imageops::resize(&*emoji, size, size, FilterType::Triangle);
+
+                    for (emoji_x, emoji_y, pixel) in emoji.enumerate_pixels() {
+                        let (target_x, target_y) = (run_x + emoji_x as i32, run_y + emoji_y as i32);
+
+                        if target_x >= 0
+                            && target_y >= 0
+                            && (target_x as u32) < coverage.width()
+                            && (target_y as u32) < coverage.height()
+                        {
+                            let target = coverage.get_pixel_mut(target_x as u32, target_y as u32);
+                            target[0] = target[0].max(pixel[3]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the position of every run of a text.
+    ///
+    /// Runs drawn with fallback fonts are moved vertically so they share the baseline
+    /// of the primary font. Emoji stand on that baseline as well, so they line up with
+    /// the glyphs whichever fonts a line mixes.
+    fn place<'t>(
+        &self,
+        x: i32,
+        y: i32,
+        scale: PxScale,
+        text: &'t str,
+    ) -> Vec<(Run<'a, 't>, i32, i32)> {
+        let primary = self.primary().as_scaled(scale);
+        let mut offset: f32 = 0.;
+
+        self.runs(text)
+            .into_iter()
+            .map(|run| {
+                let run_y = match &run {
+                    Run::Text(font, _) => primary.ascent() - font.as_scaled(scale).ascent(),
+                    Run::Emoji(_) => primary.ascent() - self.emoji_size(scale) as f32,
+                };
+                let run_x = offset;
+                offset += self.advance(&run, scale);
+
+                (run, x + run_x.round() as i32, y + run_y.round() as i32)
+            })
+            .collect()
+    }
+
+    /// Splits text into runs of characters which are drawn with the same font and emoji.
+    fn runs<'t>(&self, text: &'t str) -> Vec<Run<'a, 't>> {
+        let mut runs = Vec::new();
+        let mut start = 0;
+        let mut current: Option<&FontVec> = None;
+
+        for (index, grapheme) in text.grapheme_indices(true) {
+            if let Some(emoji) = self.emoji.and_then(|emoji| emoji.get(grapheme)) {
+                if let Some(font) = current.take() {
+                    runs.push(Run::Text(font, &text[start..index]));
+                }
+
+                runs.push(Run::Emoji(emoji));
+                start = index + grapheme.len();
+                continue;
+            }
+
+            for (char_index, c) in grapheme.char_indices() {
+                let index = index + char_index;
+                let font = match current {
+                    Some(font) if is_joiner(c) || c.is_whitespace() => font,
+                    _ => self.font_for(c),
+                };
+
+                if let Some(previous) = current {
+                    if !std::ptr::eq(previous, font) {
+                        runs.push(Run::Text(previous, &text[start..index]));
+                        start = index;
+                    }
+                }
+
+                current = Some(font);
+            }
+        }
+
+        if let Some(font) = current {
+            runs.push(Run::Text(font, &text[start..]));
+        }
+
+        runs
+    }
+
+    /// Returns the first font which has a glyph for the character.
+    ///
+    /// Characters no font supports are drawn with the primary font.
+    fn font_for(&self, c: char) -> &'a FontVec {
+        self.fonts
+            .iter()
+            .find(|font| font.glyph_id(c) != GlyphId(0))
+            .copied()
+            .unwrap_or_else(|| self.primary())
+    }
+
+    /// Returns the size of emoji images, which is the ascent of the primary font.
+    fn emoji_size(&self, scale: PxScale) -> u32 {
+        self.primary().as_scaled(scale).ascent().round().max(1.) as u32
+    }
+
+    /// Returns the horizontal advance of a run.
+    fn advance(&self, run: &Run, scale: PxScale) -> f32 {
+        match run {
+            Run::Text(font, text) => advance(font, scale, text),
+            Run::Emoji(_) => self.emoji_size(scale) as f32,
+        }
+    }
+}
+
+/// Checks whether a character modifies the previous one and must be drawn with the same font.
+fn is_joiner(c: char) -> bool {
+    matches!(c, '\u{200d}' | '\u{fe00}'..='\u{fe0f}' | '\u{0300}'..='\u{036f}')
+}
+
+/// Returns the horizontal advance of a text drawn with a single font.
+fn advance(font: &FontVec, scale: PxScale, text: &str) -> f32 {
+    let font = font.as_scaled(scale);
+    let mut width = 0.;
+    let mut last: Option<GlyphId> = None;
+
+    for c in text.chars() {
+        let glyph_id = font.glyph_id(c);
+
+        if let Some(last) = last {
+            width += font.kern(last, glyph_id);
+        }
+
+        width += font.h_advance(glyph_id);
+        last = Some(glyph_id);
+    }
+
+    width
+}
@@ -1,14 +1,37 @@
+pub mod animation;
+pub mod cache;
+pub mod color;
+pub mod effects;
+pub mod emoji;
 pub mod error;
+pub mod font;
+pub mod mask;
+pub mod output;
+pub mod picture;
+pub mod shape;
+pub mod source;
+pub mod template;
+pub mod text;
+pub mod text_template;
 
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
 };
 
-use ab_glyph::{FontVec, PxScale};
+use ab_glyph::{FontVec, InvalidFont, PxScale};
+use animation::AnimationOptions;
+use cache::{AssetCache, DEFAULT_CACHE_CAPACITY};
+use effects::TextEffects;
+use emoji::EmojiSet;
 use error::Error;
-use image::{imageops, DynamicImage, Rgba};
-use imageproc::drawing::{draw_text_mut, text_size};
+use font::{FontFamily, FontStack};
+use image::{DynamicImage, Rgba};
+use mask::Mask;
+use picture::PictureStyle;
+use shape::{Fill, Shape};
+use source::ImageSource;
+use text::TextBox;
 
 /// Represents a 2D vector with `x` and `y` coordinates.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,26 +68,192 @@ impl Vec2<i64> {
 
 /// A generator for creating and manipulating images.
 pub struct ImageGenerator {
-    /// A collection of fonts available for use in the generator.
-    fonts: HashMap<String, FontVec>,
+    /// A collection of font families available for use in the generator.
+    fonts: HashMap<String, FontFamily>,
+    /// The fonts tried for characters that no font of a family supports.
+    fallback_fonts: Vec<FontVec>,
+    /// The color emoji images drawn inline with text.
+    emoji: Option<EmojiSet>,
+    /// The decoded image files, e.g. the banners of every guild.
+    cache: AssetCache,
 }
 
 impl ImageGenerator {
     /// Creates a new `ImageGenerator` instance.
     #[must_use]
     pub fn new() -> Self {
+        Self::with_cache_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Creates a new `ImageGenerator` instance which keeps a limited number of decoded
+    /// image files in memory.
+    ///
+    /// Files are evicted in least recently used order and decoded again when they
+    /// change on disk.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of decoded files kept. `0` disables caching.
+    #[must_use]
+    pub fn with_cache_capacity(capacity: usize) -> Self {
         Self {
             fonts: HashMap::new(),
+            fallback_fonts: Vec::new(),
+            emoji: None,
+            cache: AssetCache::new(capacity),
         }
     }
 
+    /// Removes every decoded image file from the cache.
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
     /// Adds a font to the generator.
     ///
     /// # Arguments
     /// * `name` - The name of the font.
     /// * `font` - The font data.
     pub fn add_font(&mut self, name: &str, font: FontVec) {
-        self.fonts.insert(name.to_string(), font);
+        self.add_font_family(name, FontFamily::new(font));
+    }
+
+    /// Adds a font under the family and style name stored in the font file.
+    ///
+    /// Fonts are named like `Fira Sans Bold`, regular styles only by their family,
+    /// e.g. `DejaVu Sans`. A font with the same name replaces the previous one.
+    ///
+    /// # Arguments
+    /// * `data` - The contents of a TrueType or OpenType font file.
+    ///
+    /// # Returns
+    /// Returns the name the font was added under.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the data is not a valid font or has no family name.
+    pub fn add_named_font(&mut self, data: Vec<u8>) -> Result<String, Error> {
+        let name = font::font_name(&data).ok_or(Error::InvalidFont(InvalidFont))?;
+        self.add_font(&name, FontVec::try_from_vec(data)?);
+
+        Ok(name)
+    }
+
+    /// Adds every font file of a directory under its family and style name.
+    ///
+    /// Files ending in `.ttf` or `.otf` are loaded in alphabetical order, so fonts with
+    /// the same name replace each other predictably. Invalid fonts are skipped with a
+    /// warning, so a single broken file does not prevent the others from loading.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory containing the font files.
+    ///
+    /// # Returns
+    /// Returns the names of the added fonts.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the directory cannot be read.
+    #[fastrace::trace]
+    pub fn load_font_dir(&mut self, dir: impl AsRef<Path>) -> Result<Vec<String>, Error> {
+        let mut names = Vec::new();
+
+        for path in font_files(dir, &["ttf", "otf"])? {
+            match std::fs::read(&path)
+                .map_err(Error::from)
+                .and_then(|data| self.add_named_font(data))
+            {
+                Ok(name) => names.push(name),
+                Err(why) => log::warn!("Skipping font {}: {why}", path.display()),
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Adds every font file of a directory as fallback font.
+    ///
+    /// Files ending in `.ttf`, `.otf` or `.ttc` are added in alphabetical order after
+    /// the fallback fonts added before, e.g. a Noto Sans CJK font for Chinese, Japanese
+    /// and Korean text. Of font collections, the first font is used. Invalid fonts are
+    /// skipped with a warning.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory containing the font files.
+    ///
+    /// # Returns
+    /// Returns the file names of the added fonts.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the directory cannot be read.
+    #[fastrace::trace]
+    pub fn load_fallback_font_dir(&mut self, dir: impl AsRef<Path>) -> Result<Vec<String>, Error> {
+        let mut names = Vec::new();
+
+        for path in font_files(dir, &["ttf", "otf", "ttc"])? {
+            match std::fs::read(&path)
+                .map_err(Error::from)
+                .and_then(|data| Ok(FontVec::try_from_vec_and_index(data, 0)?))
+            {
+                Ok(font) => {
+                    self.add_fallback_font(font);
+                    names.push(
+                        path.file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                            .into_owned(),
+                    );
+                }
+                Err(why) => log::warn!("Skipping fallback font {}: {why}", path.display()),
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Adds a font family with its own fallback fonts to the generator.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the font family.
+    /// * `family` - The fonts of the family.
+    pub fn add_font_family(&mut self, name: &str, family: FontFamily) {
+        self.fonts.insert(name.to_string(), family);
+    }
+
+    /// Adds a fallback font which is used by every font family.
+    ///
+    /// Fallback fonts are tried in the order they were added, after all fonts of the
+    /// family, so characters like emoji or other scripts are not drawn as boxes.
+    ///
+    /// # Arguments
+    /// * `font` - The font data.
+    pub fn add_fallback_font(&mut self, font: FontVec) {
+        self.fallback_fonts.push(font);
+    }
+
+    /// Sets the color emoji images drawn inline with text.
+    ///
+    /// Emoji without an image in the set are drawn with the fonts.
+    ///
+    /// # Arguments
+    /// * `emoji` - The emoji images.
+    pub fn set_emoji(&mut self, emoji: EmojiSet) {
+        self.emoji = Some(emoji);
+    }
+
+    /// Checks whether a font with the given name has been added to the generator.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the font.
+    #[must_use]
+    pub fn has_font(&self, name: &str) -> bool {
+        self.fonts.contains_key(name)
+    }
+
+    /// Returns the names of all fonts added to the generator in alphabetical order.
+    #[must_use]
+    pub fn font_names(&self) -> Vec<&str> {
+        let mut names = self.fonts.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+
+        names
     }
 
     /// Generates an image based on the provided `ImageBuilder`.
@@ -76,57 +265,262 @@ impl ImageGenerator {
     /// Returns an [`Error`] if the image generation fails.
     #[fastrace::trace]
     pub fn generate(&self, builder: ImageBuilder) -> Result<DynamicImage, Error> {
-        let mut base_image = image::open(builder.base_image)?;
+        let mut base_image = builder.base_image.load_still(&self.cache)?;
 
         for element in &builder.elements {
             match element {
-                ImageElement::Picture { x, y, path } => {
-                    Self::overlay_image(&mut base_image, path, *x, *y)?;
-                }
-                ImageElement::Text {
+                ImageElement::Picture {
                     x,
                     y,
-                    scale,
-                    text,
-                    font_name,
-                    color,
-                    center_pivot,
-                } => self.overlay_text(
-                    &mut base_image,
-                    text,
-                    Vec2::<i32>::new(*x, *y),
-                    *scale,
-                    font_name,
-                    *color,
-                    *center_pivot,
-                )?,
+                    source,
+                    mask,
+                    style,
+                } => {
+                    let (top_image, offset) = Self::prepare_picture(
+                        &source.load_still(&self.cache)?,
+                        mask.as_ref(),
+                        style,
+                    )?;
+
+                    Self::overlay_image(
+                        &mut base_image,
+                        &top_image,
+                        Vec2::<i64>::new(x + offset.x, y + offset.y),
+                        style,
+                    );
+                }
+                ImageElement::Text { .. }
+                | ImageElement::TextBox { .. }
+                | ImageElement::Shape { .. } => {
+                    self.draw_element(&mut base_image, element)?;
+                }
             }
         }
 
         Ok(base_image)
     }
 
-    /// Overlays an image onto the base image.
+    /// Checks whether the base image or any picture of the `ImageBuilder` is animated.
+    ///
+    /// Animated files are not decoded completely, so this is much cheaper than
+    /// [`Self::generate_animated`].
     ///
     /// # Arguments
-    /// * `base_image` - The base image to overlay onto.
-    /// * `top_image_path` - The path to the image to overlay.
-    /// * `x` - The x-coordinate of the overlay position.
-    /// * `y` - The y-coordinate of the overlay position.
+    /// * `builder` - The `ImageBuilder` containing the base image and elements to overlay.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if an image cannot be read or decoded.
+    #[fastrace::trace]
+    pub fn is_animated(&self, builder: &ImageBuilder) -> Result<bool, Error> {
+        if builder.base_image.is_animated(&self.cache)? {
+            return Ok(true);
+        }
+
+        for element in &builder.elements {
+            if let ImageElement::Picture { source, .. } = element {
+                if source.is_animated(&self.cache)? {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Generates an animated image based on the provided `ImageBuilder`.
+    ///
+    /// Every frame of animated GIF or WebP pictures, including the base image, is
+    /// composed with the other elements. The timeline follows the longest animation
+    /// and shorter animations are looped.
+    ///
+    /// # Arguments
+    /// * `builder` - The `ImageBuilder` containing the base image and elements to overlay.
+    /// * `options` - The output format and limits of the animation.
+    ///
+    /// # Returns
+    /// Returns the encoded animation, or `None` if none of the images is animated.
     ///
     /// # Errors
-    /// Returns an [`Error`] if the overlay operation fails.
+    /// Returns an [`Error`] if the image generation fails or the animation does not
+    /// fit into `options.max_bytes`.
     #[fastrace::trace]
-    fn overlay_image<T: AsRef<Path>>(
+    pub fn generate_animated(
+        &self,
+        builder: &ImageBuilder,
+        options: &AnimationOptions,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        if !self.is_animated(builder)? {
+            return Ok(None);
+        }
+
+        let base_sequence = builder.base_image.load_frames(&self.cache)?;
+        let mut sequences = Vec::with_capacity(builder.elements.len());
+
+        for element in &builder.elements {
+            let sequence = match element {
+                ImageElement::Picture {
+                    source,
+                    mask,
+                    style,
+                    ..
+                } => {
+                    let mut offset = Vec2::<i64>::new(0, 0);
+                    let sequence = source.load_frames(&self.cache)?.try_map(|frame| {
+                        let (frame, frame_offset) =
+                            Self::prepare_picture(frame, mask.as_ref(), style)?;
+                        offset = frame_offset;
+
+                        Ok(frame)
+                    })?;
+
+                    Some((sequence, offset))
+                }
+                ImageElement::Text { .. }
+                | ImageElement::TextBox { .. }
+                | ImageElement::Shape { .. } => None,
+            };
+
+            sequences.push(sequence);
+        }
+
+        let Some(longest) = std::iter::once(base_sequence.as_ref())
+            .chain(sequences.iter().flatten().map(|(sequence, _)| sequence))
+            .filter(|sequence| sequence.is_animated())
+            .max_by_key(|sequence| sequence.duration())
+        else {
+            return Ok(None);
+        };
+
+        let mut frames = Vec::new();
+
+        for (time, delay) in longest.timeline(options.max_frames) {
+            let mut frame = base_sequence.frame_at(time).clone();
+
+            for (element, sequence) in builder.elements.iter().zip(&sequences) {
+                match (element, sequence) {
+                    (ImageElement::Picture { x, y, style, .. }, Some((sequence, offset))) => {
+                        Self::overlay_image(
+                            &mut frame,
+                            sequence.frame_at(time),
+                            Vec2::<i64>::new(x + offset.x, y + offset.y),
+                            style,
+                        );
+                    }
+                    _ => self.draw_element(&mut frame, element)?,
+                }
+            }
+
+            frames.push((frame, delay));
+        }
+
+        animation::encode_within_budget(frames, options).map(Some)
+    }
+
+    /// Draws a text or shape element onto the base image. Pictures are ignored.
+    ///
+    /// # Arguments
+    /// * `base_image` - The base image to draw onto.
+    /// * `element` - The element to draw.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the font is not found.
+    fn draw_element(
+        &self,
         base_image: &mut DynamicImage,
-        top_image_path: T,
-        x: i64,
-        y: i64,
+        element: &ImageElement,
     ) -> Result<(), Error> {
-        let top_image = image::open(top_image_path)?;
-        imageops::overlay(base_image, &top_image, x, y);
+        match element {
+            ImageElement::Text {
+                x,
+                y,
+                scale,
+                text,
+                font_name,
+                color,
+                center_pivot,
+                effects,
+            } => self.overlay_text(
+                base_image,
+                text,
+                Vec2::<i32>::new(*x, *y),
+                *scale,
+                font_name,
+                *color,
+                *center_pivot,
+                effects,
+            ),
+            ImageElement::TextBox {
+                text,
+                bounds,
+                font_name,
+                color,
+                effects,
+            } => self.overlay_text_box(base_image, text, bounds, font_name, *color, effects),
+            ImageElement::Shape {
+                shape,
+                fill,
+                opacity,
+            } => {
+                shape::draw_shape(base_image, shape, fill, *opacity);
+                Ok(())
+            }
+            ImageElement::Picture { .. } => Ok(()),
+        }
+    }
 
-        Ok(())
+    /// Scales, masks and rotates a picture before it is overlaid.
+    ///
+    /// # Arguments
+    /// * `image` - The picture.
+    /// * `mask` - An optional mask, applied after scaling so it fits the target size.
+    /// * `style` - The size and rotation of the picture.
+    ///
+    /// # Returns
+    /// Returns the prepared picture together with its offset to the element position.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the mask cannot be applied.
+    fn prepare_picture(
+        image: &DynamicImage,
+        mask: Option<&Mask>,
+        style: &PictureStyle,
+    ) -> Result<(DynamicImage, Vec2<i64>), Error> {
+        let (mut image, resize_x, resize_y) = style.resize(image);
+
+        if let Some(mask) = mask {
+            image = mask.apply(&image)?;
+        }
+
+        let (image, rotate_x, rotate_y) = style.rotate(image);
+
+        Ok((
+            image,
+            Vec2::<i64>::new(resize_x + rotate_x, resize_y + rotate_y),
+        ))
+    }
+
+    /// Overlays an image onto the base image.
+    ///
+    /// # Arguments
+    /// * `base_image` - The base image to overlay onto.
+    /// * `top_image` - The image to overlay.
+    /// * `position` - The overlay position.
+    /// * `style` - The opacity and blend mode of the image.
+    #[fastrace::trace]
+    fn overlay_image(
+        base_image: &mut DynamicImage,
+        top_image: &DynamicImage,
+        position: Vec2<i64>,
+        style: &PictureStyle,
+    ) {
+        picture::draw_picture(
+            base_image,
+            top_image,
+            position.x,
+            position.y,
+            style.opacity,
+            style.blend,
+        );
     }
 
     /// Overlays text onto the base image.
@@ -139,6 +533,7 @@ impl ImageGenerator {
     /// * `font_name` - The name of the font to use.
     /// * `color` - The color of the text.
     /// * `center_pivot` - Whether to center the text around the position.
+    /// * `effects` - The stroke, shadow and glow drawn behind the text.
     ///
     /// # Errors
     /// Returns an [`Error`] if the font is not found or the overlay operation fails.
@@ -152,30 +547,77 @@ impl ImageGenerator {
         font_name: &str,
         color: Rgba<u8>,
         center_pivot: bool,
+        effects: &TextEffects,
     ) -> Result<(), Error> {
-        if let Some(font) = self.fonts.get(font_name) {
-            let (text_x, _) = text_size(scale, font, text);
-
-            if center_pivot {
-                draw_text_mut(
-                    base_image,
-                    color,
-                    position.x - text_x as i32 / 2,
-                    position.y,
-                    scale,
-                    font,
-                    text,
-                );
-            } else {
-                draw_text_mut(base_image, color, position.x, position.y, scale, font, text);
+        let fonts = self.font_stack(font_name)?;
+        let x = if center_pivot {
+            position.x - fonts.text_width(scale, text) as i32 / 2
+        } else {
+            position.x
+        };
+
+        effects.apply(base_image, |coverage| {
+            fonts.draw_coverage(coverage, x, position.y, scale, text);
+        });
+        fonts.draw(base_image, color, x, position.y, scale, text);
+
+        Ok(())
+    }
+
+    /// Overlays text fitted into a box onto the base image.
+    ///
+    /// # Arguments
+    /// * `base_image` - The base image to overlay text onto.
+    /// * `text` - The text to overlay.
+    /// * `bounds` - The box the text is wrapped, shrunk and truncated to fit into.
+    /// * `font_name` - The name of the font to use.
+    /// * `color` - The color of the text.
+    /// * `effects` - The stroke, shadow and glow drawn behind the text.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the font is not found.
+    #[fastrace::trace]
+    fn overlay_text_box(
+        &self,
+        base_image: &mut DynamicImage,
+        text: &str,
+        bounds: &TextBox,
+        font_name: &str,
+        color: Rgba<u8>,
+        effects: &TextEffects,
+    ) -> Result<(), Error> {
+        let fonts = self.font_stack(font_name)?;
+        let layout = bounds.layout(&fonts, text);
+
+        effects.apply(base_image, |coverage| {
+            for (line, x, y) in &layout.lines {
+                fonts.draw_coverage(coverage, *x, *y, layout.scale, line);
             }
+        });
 
-            return Ok(());
+        for (line, x, y) in &layout.lines {
+            fonts.draw(base_image, color, *x, *y, layout.scale, line);
         }
 
-        Err(Error::FontNotFound(format!(
-            "The font '{font_name}' was not found. Please make sure it is loaded."
-        )))
+        Ok(())
+    }
+
+    /// Returns the fonts used to draw text with the given font family.
+    ///
+    /// # Arguments
+    /// * `font_name` - The name of the font family.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the font family is not found.
+    fn font_stack(&self, font_name: &str) -> Result<FontStack<'_>, Error> {
+        self.fonts
+            .get(font_name)
+            .map(|family| FontStack::new(family, &self.fallback_fonts, self.emoji.as_ref()))
+            .ok_or_else(|| {
+                Error::FontNotFound(format!(
+                    "The font '{font_name}' was not found. Please make sure it is loaded."
+                ))
+            })
     }
 }
 
@@ -187,6 +629,7 @@ impl Default for ImageGenerator {
 }
 
 /// Represents an element to overlay on an image.
+#[derive(Clone)]
 pub enum ImageElement {
     /// Represents an image to overlay.
     Picture {
@@ -194,8 +637,12 @@ pub enum ImageElement {
         x: i64,
         /// The y-coordinate of the overlay position.
         y: i64,
-        /// The path to the image to overlay.
-        path: PathBuf,
+        /// The source of the image to overlay.
+        source: ImageSource,
+        /// An optional mask to cut out the image before overlaying it.
+        mask: Option<Mask>,
+        /// The size, rotation, opacity and blend mode of the image.
+        style: PictureStyle,
     },
     /// Represents text to overlay.
     Text {
@@ -213,13 +660,38 @@ pub enum ImageElement {
         color: Rgba<u8>,
         /// Whether to center the text around the position.
         center_pivot: bool,
+        /// The stroke, shadow and glow drawn behind the text.
+        effects: TextEffects,
+    },
+    /// Represents text fitted into a box.
+    TextBox {
+        /// The text content.
+        text: String,
+        /// The box the text is wrapped, shrunk and truncated to fit into.
+        bounds: TextBox,
+        /// The name of the font to use.
+        font_name: String,
+        /// The color of the text.
+        color: Rgba<u8>,
+        /// The stroke, shadow and glow drawn behind the text.
+        effects: TextEffects,
+    },
+    /// Represents a filled shape.
+    Shape {
+        /// The geometry of the shape.
+        shape: Shape,
+        /// The colors of the shape.
+        fill: Fill,
+        /// The opacity of the whole shape, from `0.0` to `1.0`.
+        opacity: f32,
     },
 }
 
 /// A builder for constructing images with overlays.
+#[derive(Clone)]
 pub struct ImageBuilder {
-    /// The path to the base image.
-    pub base_image: PathBuf,
+    /// The source of the base image.
+    pub base_image: ImageSource,
     /// The elements to overlay on the base image.
     pub elements: Vec<ImageElement>,
 }
@@ -228,10 +700,10 @@ impl ImageBuilder {
     /// Creates a new `ImageBuilder` with the specified base image.
     ///
     /// # Arguments
-    /// * `base_image` - The path to the base image.
-    pub fn new<T: AsRef<Path>>(base_image: T) -> Self {
+    /// * `base_image` - The source of the base image, e.g. a path or an encoded buffer.
+    pub fn new<T: Into<ImageSource>>(base_image: T) -> Self {
         Self {
-            base_image: base_image.as_ref().to_path_buf(),
+            base_image: base_image.into(),
             elements: Vec::new(),
         }
     }
@@ -239,10 +711,10 @@ impl ImageBuilder {
     /// Sets the base image for the builder.
     ///
     /// # Arguments
-    /// * `path` - The path to the base image.
+    /// * `source` - The source of the base image, e.g. a path or an encoded buffer.
     #[must_use]
-    pub fn with_base_image<T: AsRef<Path>>(mut self, path: T) -> Self {
-        self.base_image = path.as_ref().to_path_buf();
+    pub fn with_base_image<T: Into<ImageSource>>(mut self, source: T) -> Self {
+        self.base_image = source.into();
 
         self
     }
@@ -250,15 +722,43 @@ impl ImageBuilder {
     /// Adds an image overlay to the builder.
     ///
     /// # Arguments
-    /// * `path` - The path to the image to overlay.
+    /// * `source` - The source of the image to overlay, e.g. a path or an encoded buffer.
     /// * `x` - The x-coordinate of the overlay position.
     /// * `y` - The y-coordinate of the overlay position.
     #[must_use]
-    pub fn add_image<T: AsRef<Path>>(mut self, path: T, x: i64, y: i64) -> Self {
+    pub fn add_image<T: Into<ImageSource>>(mut self, source: T, x: i64, y: i64) -> Self {
         self.elements.push(ImageElement::Picture {
             x,
             y,
-            path: path.as_ref().to_path_buf(),
+            source: source.into(),
+            mask: None,
+            style: PictureStyle::default(),
+        });
+
+        self
+    }
+
+    /// Adds an image overlay which is cut out by a mask to the builder.
+    ///
+    /// # Arguments
+    /// * `source` - The source of the image to overlay, e.g. a path or an encoded buffer.
+    /// * `x` - The x-coordinate of the overlay position.
+    /// * `y` - The y-coordinate of the overlay position.
+    /// * `mask` - The mask to apply to the image.
+    #[must_use]
+    pub fn add_masked_image<T: Into<ImageSource>>(
+        mut self,
+        source: T,
+        x: i64,
+        y: i64,
+        mask: Mask,
+    ) -> Self {
+        self.elements.push(ImageElement::Picture {
+            x,
+            y,
+            source: source.into(),
+            mask: Some(mask),
+            style: PictureStyle::default(),
         });
 
         self
@@ -293,8 +793,116 @@ impl ImageBuilder {
             font_name: font_name.to_string(),
             color,
             center_pivot,
+            effects: TextEffects::default(),
+        });
+
+        self
+    }
+
+    /// Adds text which is fitted into a box to the builder.
+    ///
+    /// # Arguments
+    /// * `text` - The text to overlay.
+    /// * `bounds` - The box the text is wrapped, shrunk and truncated to fit into.
+    /// * `font_name` - The name of the font to use.
+    /// * `color` - The color of the text.
+    #[must_use]
+    pub fn add_text_box(
+        mut self,
+        text: &str,
+        bounds: TextBox,
+        font_name: &str,
+        color: Rgba<u8>,
+    ) -> Self {
+        self.elements.push(ImageElement::TextBox {
+            text: text.to_string(),
+            bounds,
+            font_name: font_name.to_string(),
+            color,
+            effects: TextEffects::default(),
         });
 
         self
     }
+
+    /// Adds a filled shape to the builder.
+    ///
+    /// # Arguments
+    /// * `shape` - The geometry of the shape.
+    /// * `fill` - The colors of the shape.
+    /// * `opacity` - The opacity of the whole shape, from `0.0` to `1.0`.
+    #[must_use]
+    pub fn add_shape(mut self, shape: Shape, fill: Fill, opacity: f32) -> Self {
+        self.elements.push(ImageElement::Shape {
+            shape,
+            fill,
+            opacity,
+        });
+
+        self
+    }
+
+    /// Sets the size, rotation, opacity and blend mode of the picture added last.
+    ///
+    /// Call it right after [`ImageBuilder::add_image`] or [`ImageBuilder::add_masked_image`].
+    /// If the last element is not a picture, the builder is returned unchanged.
+    ///
+    /// # Arguments
+    /// * `picture_style` - The style of the picture.
+    #[must_use]
+    pub fn with_picture_style(mut self, picture_style: PictureStyle) -> Self {
+        if let Some(ImageElement::Picture { style, .. }) = self.elements.last_mut() {
+            *style = picture_style;
+        }
+
+        self
+    }
+
+    /// Sets the stroke, shadow and glow of the text element added last.
+    ///
+    /// Call it right after [`ImageBuilder::add_text`] or [`ImageBuilder::add_text_box`].
+    /// If the last element is not text, the builder is returned unchanged.
+    ///
+    /// # Arguments
+    /// * `text_effects` - The effects to draw behind the text.
+    #[must_use]
+    pub fn with_text_effects(mut self, text_effects: TextEffects) -> Self {
+        if let Some(ImageElement::Text { effects, .. } | ImageElement::TextBox { effects, .. }) =
+            self.elements.last_mut()
+        {
+            *effects = text_effects;
+        }
+
+        self
+    }
+}
+
+/// Returns the font files of a directory in alphabetical order.
+///
+/// # Arguments
+/// * `dir` - The directory containing the font files.
+/// * `extensions` - The accepted file extensions, compared case-insensitively.
+///
+/// # Errors
+/// Returns an [`Error`] if the directory cannot be read.
+fn font_files(dir: impl AsRef<Path>, extensions: &[&str]) -> Result<Vec<PathBuf>, Error> {
+    let mut paths = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+
+    paths.retain(|path| {
+        let is_font = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                extensions
+                    .iter()
+                    .any(|accepted| extension.eq_ignore_ascii_case(accepted))
+            });
+
+        is_font && path.is_file()
+    });
+
+    Ok(paths)
 }
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use image::{imageops::FilterType, DynamicImage, GenericImageView, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// A shape used to cut out a picture before it is drawn.
+///
+/// Masks are scaled to the size of the picture they are applied to, so the same
+/// mask works for avatars of any resolution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Mask {
+    /// The largest circle that fits into the picture.
+    Circle,
+    /// A rectangle covering the picture with rounded corners.
+    RoundedRectangle {
+        /// The radius of the corners in pixels.
+        radius: u32,
+    },
+    /// An image whose alpha channel is used as the mask.
+    ///
+    /// Images without an alpha channel use their brightness instead.
+    Image {
+        /// The path to the mask image.
+        path: PathBuf,
+    },
+}
+
+impl Mask {
+    /// Applies the mask to an image by scaling down the alpha of every pixel.
+    ///
+    /// # Arguments
+    /// * `image` - The image to mask.
+    ///
+    /// # Returns
+    /// Returns the masked image.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the mask image cannot be loaded.
+    #[fastrace::trace]
+    pub fn apply(&self, image: &DynamicImage) -> Result<DynamicImage, Error> {
+        let mut masked = image.to_rgba8();
+        let (width, height) = masked.dimensions();
+
+        match self {
+            Self::Circle => {
+                let radius = width.min(height) as f32 / 2.;
+                Self::apply_coverage(&mut masked, |x, y| {
+                    let dx = x - width as f32 / 2.;
+                    let dy = y - height as f32 / 2.;
+
+                    radius - (dx * dx + dy * dy).sqrt()
+                });
+            }
+            Self::RoundedRectangle { radius } => {
+                let radius = (*radius).min(width / 2).min(height / 2) as f32;
+                Self::apply_coverage(&mut masked, |x, y| {
+                    let dx = (radius - x).max(x - (width as f32 - radius)).max(0.);
+                    let dy = (radius - y).max(y - (height as f32 - radius)).max(0.);
+
+                    radius - (dx * dx + dy * dy).sqrt()
+                });
+            }
+            Self::Image { path } => {
+                let mask = image::open(path)?.resize_exact(width, height, FilterType::Triangle);
+                let use_alpha = mask.color().has_alpha();
+
+                for (x, y, pixel) in masked.enumerate_pixels_mut() {
+                    let mask_pixel = mask.get_pixel(x, y);
+                    let coverage = if use_alpha {
+                        mask_pixel[3]
+                    } else {
+                        let [r, g, b, _] = mask_pixel.0;
+                        ((u16::from(r) + u16::from(g) + u16::from(b)) / 3) as u8
+                    };
+
+                    pixel[3] = (u16::from(pixel[3]) * u16::from(coverage) / 255) as u8;
+                }
+            }
+        }
+
+        Ok(DynamicImage::ImageRgba8(masked))
+    }
+
+    /// Scales the alpha of every pixel by the coverage of a shape.
+    ///
+    /// # Arguments
+    /// * `image` - The image to mask.
+    /// * `distance` - Returns the signed distance of a pixel center to the shape edge,
+    ///   positive inside the shape. Pixels within half a pixel of the edge are blended
+    ///   to get anti-aliased edges.
+    fn apply_coverage(image: &mut RgbaImage, distance: impl Fn(f32, f32) -> f32) {
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let coverage = (distance(x as f32 + 0.5, y as f32 + 0.5) + 0.5).clamp(0., 1.);
+            pixel[3] = (f32::from(pixel[3]) * coverage).round() as u8;
+        }
+    }
+}
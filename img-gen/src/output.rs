@@ -0,0 +1,182 @@
+use std::io::Cursor;
+
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// The lowest quality lossy formats are stepped down to before the image is shrunk.
+const MIN_QUALITY: u8 = 40;
+/// How much the quality is lowered per step.
+const QUALITY_STEP: u8 = 15;
+/// The factor the dimensions are multiplied with per step.
+const SCALE_STEP: f32 = 0.8;
+/// The smallest width or height an image is shrunk to.
+const MIN_DIMENSION: u32 = 64;
+
+/// The file format of a generated still image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// A lossless PNG.
+    Png,
+    /// A lossy WebP.
+    WebP {
+        /// The quality from `0` to `100`.
+        quality: u8,
+    },
+    /// A lossless WebP.
+    WebPLossless,
+    /// A JPEG. Transparency is lost.
+    Jpeg {
+        /// The quality from `1` to `100`.
+        quality: u8,
+    },
+}
+
+impl OutputFormat {
+    /// Returns the file extension of the format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::WebP { .. } | Self::WebPLossless => "webp",
+            Self::Jpeg { .. } => "jpg",
+        }
+    }
+
+    /// Returns the same format with a lower quality, or `None` if the format is
+    /// lossless or already at the lowest quality.
+    const fn lower_quality(self) -> Option<Self> {
+        match self {
+            Self::WebP { quality } if quality > MIN_QUALITY => Some(Self::WebP {
+                quality: lower(quality),
+            }),
+            Self::Jpeg { quality } if quality > MIN_QUALITY => Some(Self::Jpeg {
+                quality: lower(quality),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Lowers a quality by one step without going below the minimum quality.
+const fn lower(quality: u8) -> u8 {
+    let quality = quality.saturating_sub(QUALITY_STEP);
+
+    if quality < MIN_QUALITY {
+        MIN_QUALITY
+    } else {
+        quality
+    }
+}
+
+/// Options for encoding generated still images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputOptions {
+    /// The file format of the output.
+    pub format: OutputFormat,
+    /// The maximum size of the encoded output in bytes, or `None` for no limit.
+    ///
+    /// Lossy formats lower their quality first, then the image is shrunk until the
+    /// output fits. If even the smallest image is too large, an [`Error::OutputTooLarge`]
+    /// is returned.
+    pub max_bytes: Option<usize>,
+}
+
+impl Default for OutputOptions {
+    /// Creates options for PNG output that stays below Discord's upload limit.
+    fn default() -> Self {
+        Self {
+            format: OutputFormat::Png,
+            max_bytes: Some(8 * 1024 * 1024),
+        }
+    }
+}
+
+/// Encodes a generated image, stepping quality and dimensions down until it fits.
+///
+/// # Arguments
+/// * `image` - The image to encode.
+/// * `options` - The output format and size limit.
+///
+/// # Returns
+/// Returns the encoded image.
+///
+/// # Errors
+/// Returns an [`Error`] if encoding fails or the image does not fit into `options.max_bytes`.
+#[fastrace::trace]
+pub fn encode(image: &DynamicImage, options: &OutputOptions) -> Result<Vec<u8>, Error> {
+    let mut format = options.format;
+    let mut resized: Option<DynamicImage> = None;
+
+    loop {
+        let current = resized.as_ref().unwrap_or(image);
+        let encoded = encode_as(current, format)?;
+
+        let Some(max_bytes) = options.max_bytes else {
+            return Ok(encoded);
+        };
+
+        if encoded.len() <= max_bytes {
+            return Ok(encoded);
+        }
+
+        log::debug!(
+            "{}x{} image as {format:?} is {} bytes, reducing size.",
+            current.width(),
+            current.height(),
+            encoded.len()
+        );
+
+        if let Some(lower_format) = format.lower_quality() {
+            format = lower_format;
+            continue;
+        }
+
+        let width = (current.width() as f32 * SCALE_STEP) as u32;
+        let height = (current.height() as f32 * SCALE_STEP) as u32;
+
+        if width.min(height) < MIN_DIMENSION {
+            return Err(Error::OutputTooLarge(encoded.len()));
+        }
+
+        resized = Some(image.resize_exact(width, height, FilterType::Triangle));
+    }
+}
+
+/// Encodes an image in a single format.
+///
+/// # Errors
+/// Returns an [`Error`] if encoding fails.
+fn encode_as(image: &DynamicImage, format: OutputFormat) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+
+    match format {
+        OutputFormat::Png => image.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png)?,
+        OutputFormat::WebP { quality } => {
+            return encode_webp(image, false, f32::from(quality.min(100)));
+        }
+        OutputFormat::WebPLossless => return encode_webp(image, true, 100.),
+        OutputFormat::Jpeg { quality } => {
+            JpegEncoder::new_with_quality(&mut buffer, quality.clamp(1, 100))
+                .encode_image(&image.to_rgb8())?;
+        }
+    }
+
+    Ok(buffer)
+}
+
+/// Encodes an image as WebP.
+///
+/// # Errors
+/// Returns an [`Error`] if encoding fails.
+fn encode_webp(image: &DynamicImage, lossless: bool, quality: f32) -> Result<Vec<u8>, Error> {
+    let rgba = image.to_rgba8();
+
+    let encoded = webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height())
+        .encode_simple(lossless, quality)
+        .map_err(|why| Error::WebPError(format!("{why:?}")))?;
+
+    Ok(encoded.to_vec())
+}
@@ -0,0 +1,215 @@
+use image::{
+    imageops::{self, FilterType},
+    DynamicImage, GenericImage, GenericImageView, Pixel, Rgba, RgbaImage,
+};
+use imageproc::geometric_transformations::{rotate_about_center, Interpolation};
+use serde::{Deserialize, Serialize};
+
+/// How a picture is scaled into its target size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeMode {
+    /// The whole picture is shown and centered in the target size, keeping its aspect ratio.
+    Fit,
+    /// The picture covers the target size, keeping its aspect ratio. Overflowing parts
+    /// are cut off.
+    Fill,
+    /// The picture is scaled to exactly the target size, ignoring its aspect ratio.
+    #[default]
+    Stretch,
+}
+
+/// The size a picture is scaled to before it is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PictureSize {
+    /// The target width in pixels.
+    pub width: u32,
+    /// The target height in pixels.
+    pub height: u32,
+    /// How the picture is scaled into the target size.
+    #[serde(default)]
+    pub mode: ResizeMode,
+}
+
+/// How the colors of a picture are combined with the image below.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlendMode {
+    /// The picture is drawn over the image below.
+    #[default]
+    Normal,
+    /// The colors are multiplied, which darkens the image.
+    Multiply,
+    /// The inverted colors are multiplied, which lightens the image.
+    Screen,
+    /// Multiply for dark and screen for light areas of the image below, which
+    /// increases the contrast.
+    Overlay,
+}
+
+impl BlendMode {
+    /// Blends a single color channel.
+    ///
+    /// # Arguments
+    /// * `base` - The channel of the image below, from `0.0` to `1.0`.
+    /// * `top` - The channel of the picture, from `0.0` to `1.0`.
+    fn blend(self, base: f32, top: f32) -> f32 {
+        match self {
+            Self::Normal => top,
+            Self::Multiply => base * top,
+            Self::Screen => 1. - (1. - base) * (1. - top),
+            Self::Overlay => {
+                if base < 0.5 {
+                    2. * base * top
+                } else {
+                    1. - 2. * (1. - base) * (1. - top)
+                }
+            }
+        }
+    }
+}
+
+/// The size, rotation, opacity and blend mode of a picture element.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PictureStyle {
+    /// The size the picture is scaled to, or `None` to keep its own size.
+    #[serde(default)]
+    pub size: Option<PictureSize>,
+    /// The clockwise rotation around the center of the picture in degrees.
+    #[serde(default)]
+    pub rotation: f32,
+    /// The opacity of the picture, from `0.0` to `1.0`.
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    /// How the colors of the picture are combined with the image below.
+    #[serde(default)]
+    pub blend: BlendMode,
+}
+
+/// The default opacity of pictures.
+const fn default_opacity() -> f32 {
+    1.
+}
+
+impl Default for PictureStyle {
+    /// Creates a style which draws the picture unchanged.
+    fn default() -> Self {
+        Self {
+            size: None,
+            rotation: 0.,
+            opacity: 1.,
+            blend: BlendMode::Normal,
+        }
+    }
+}
+
+impl PictureStyle {
+    /// Scales a picture to the target size.
+    ///
+    /// # Returns
+    /// Returns the scaled picture together with its offset to the element position.
+    /// The offset centers pictures which are smaller than the target size.
+    pub(crate) fn resize(&self, image: &DynamicImage) -> (DynamicImage, i64, i64) {
+        let Some(size) = self.size else {
+            return (image.clone(), 0, 0);
+        };
+
+        let (width, height) = (size.width.max(1), size.height.max(1));
+        let resized = match size.mode {
+            ResizeMode::Fit => image.resize(width, height, FilterType::Triangle),
+            ResizeMode::Fill => image.resize_to_fill(width, height, FilterType::Triangle),
+            ResizeMode::Stretch => image.resize_exact(width, height, FilterType::Triangle),
+        };
+
+        let offset_x = (i64::from(width) - i64::from(resized.width())) / 2;
+        let offset_y = (i64::from(height) - i64::from(resized.height())) / 2;
+
+        (resized, offset_x, offset_y)
+    }
+
+    /// Rotates a picture around its center.
+    ///
+    /// The canvas grows so no corner is cut off.
+    ///
+    /// # Returns
+    /// Returns the rotated picture together with its offset to the unrotated picture.
+    pub(crate) fn rotate(&self, image: DynamicImage) -> (DynamicImage, i64, i64) {
+        if self.rotation % 360. == 0. {
+            return (image, 0, 0);
+        }
+
+        let (width, height) = (image.width(), image.height());
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let rotated_width = (width as f32 * cos.abs() + height as f32 * sin.abs()).ceil() as u32;
+        let rotated_height = (width as f32 * sin.abs() + height as f32 * cos.abs()).ceil() as u32;
+
+        let offset_x = (i64::from(rotated_width) - i64::from(width)) / 2;
+        let offset_y = (i64::from(rotated_height) - i64::from(height)) / 2;
+
+        let mut canvas = RgbaImage::new(rotated_width, rotated_height);
+        imageops::overlay(&mut canvas, &image.to_rgba8(), offset_x, offset_y);
+
+        let rotated = rotate_about_center(
+            &canvas,
+            self.rotation.to_radians(),
+            Interpolation::Bilinear,
+            Rgba([0, 0, 0, 0]),
+        );
+
+        (DynamicImage::ImageRgba8(rotated), -offset_x, -offset_y)
+    }
+}
+
+/// Draws a picture onto an image with an opacity and blend mode.
+///
+/// # Arguments
+/// * `base_image` - The image to draw onto.
+/// * `top_image` - The picture to draw.
+/// * `x` - The x-coordinate of the picture position.
+/// * `y` - The y-coordinate of the picture position.
+/// * `opacity` - The opacity of the picture, from `0.0` to `1.0`.
+/// * `blend` - How the colors of the picture are combined with the image below.
+#[fastrace::trace]
+pub(crate) fn draw_picture(
+    base_image: &mut DynamicImage,
+    top_image: &DynamicImage,
+    x: i64,
+    y: i64,
+    opacity: f32,
+    blend: BlendMode,
+) {
+    let opacity = opacity.clamp(0., 1.);
+
+    if blend == BlendMode::Normal && opacity >= 1. {
+        imageops::overlay(base_image, top_image, x, y);
+        return;
+    }
+
+    for (top_x, top_y, top) in top_image.pixels() {
+        let (Ok(target_x), Ok(target_y)) = (
+            u32::try_from(x + i64::from(top_x)),
+            u32::try_from(y + i64::from(top_y)),
+        ) else {
+            continue;
+        };
+
+        if !base_image.in_bounds(target_x, target_y) || top[3] == 0 {
+            continue;
+        }
+
+        let mut pixel = base_image.get_pixel(target_x, target_y);
+        let base_alpha = f32::from(pixel[3]) / 255.;
+
+        let channel = |index: usize| {
+            let base = f32::from(pixel[index]) / 255.;
+            let top = f32::from(top[index]) / 255.;
+            let mixed = top * (1. - base_alpha) + blend.blend(base, top) * base_alpha;
+
+            (mixed * 255.).round().clamp(0., 255.) as u8
+        };
+
+        let alpha = (f32::from(top[3]) * opacity).round() as u8;
+        pixel.blend(&Rgba([channel(0), channel(1), channel(2), alpha]));
+        base_image.put_pixel(target_x, target_y, pixel);
+    }
+}
@@ -0,0 +1,345 @@
+use image::{DynamicImage, GenericImage, GenericImageView, Pixel, Rgba};
+use serde::{Deserialize, Serialize};
+
+use crate::color::Color;
+
+/// A geometric shape drawn directly onto an image.
+///
+/// Edges are anti-aliased, so shapes look smooth at any size.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Shape {
+    /// An axis aligned rectangle.
+    Rectangle {
+        /// The x-coordinate of the left edge.
+        x: i32,
+        /// The y-coordinate of the top edge.
+        y: i32,
+        /// The width in pixels.
+        width: u32,
+        /// The height in pixels.
+        height: u32,
+    },
+    /// An axis aligned rectangle with rounded corners.
+    RoundedRectangle {
+        /// The x-coordinate of the left edge.
+        x: i32,
+        /// The y-coordinate of the top edge.
+        y: i32,
+        /// The width in pixels.
+        width: u32,
+        /// The height in pixels.
+        height: u32,
+        /// The radius of the corners in pixels.
+        radius: u32,
+    },
+    /// A circle.
+    Circle {
+        /// The x-coordinate of the center.
+        x: i32,
+        /// The y-coordinate of the center.
+        y: i32,
+        /// The radius in pixels.
+        radius: u32,
+    },
+    /// A straight line with flat ends.
+    Line {
+        /// The x-coordinate of the start point.
+        x1: i32,
+        /// The y-coordinate of the start point.
+        y1: i32,
+        /// The x-coordinate of the end point.
+        x2: i32,
+        /// The y-coordinate of the end point.
+        y2: i32,
+        /// The width of the line in pixels.
+        width: u32,
+    },
+}
+
+impl Shape {
+    /// Returns the bounding box of the shape as `x`, `y`, `width` and `height`.
+    fn bounds(&self) -> (f32, f32, f32, f32) {
+        match *self {
+            Self::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            }
+            | Self::RoundedRectangle {
+                x,
+                y,
+                width,
+                height,
+                ..
+            } => (x as f32, y as f32, width as f32, height as f32),
+            Self::Circle { x, y, radius } => {
+                let radius = radius as f32;
+                (
+                    x as f32 - radius,
+                    y as f32 - radius,
+                    radius * 2.,
+                    radius * 2.,
+                )
+            }
+            Self::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                width,
+            } => {
+                let half_width = width as f32 / 2.;
+                let (left, right) = (x1.min(x2) as f32, x1.max(x2) as f32);
+                let (top, bottom) = (y1.min(y2) as f32, y1.max(y2) as f32);
+
+                (
+                    left - half_width,
+                    top - half_width,
+                    right - left + half_width * 2.,
+                    bottom - top + half_width * 2.,
+                )
+            }
+        }
+    }
+
+    /// Returns the signed distance of a point to the edge of the shape, positive inside.
+    ///
+    /// # Arguments
+    /// * `px` - The x-coordinate of the point.
+    /// * `py` - The y-coordinate of the point.
+    fn distance(&self, px: f32, py: f32) -> f32 {
+        match *self {
+            Self::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let (left, top) = (x as f32, y as f32);
+                let (right, bottom) = (left + width as f32, top + height as f32);
+
+                (px - left).min(right - px).min(py - top).min(bottom - py)
+            }
+            Self::RoundedRectangle {
+                x,
+                y,
+                width,
+                height,
+                radius,
+            } => {
+                let radius = radius.min(width / 2).min(height / 2) as f32;
+                let (px, py) = (px - x as f32, py - y as f32);
+                let (width, height) = (width as f32, height as f32);
+
+                if radius == 0. {
+                    return px.min(width - px).min(py).min(height - py);
+                }
+
+                let dx = (radius - px).max(px - (width - radius));
+                let dy = (radius - py).max(py - (height - radius));
+
+                if dx > 0. && dy > 0. {
+                    radius - (dx * dx + dy * dy).sqrt()
+                } else {
+                    px.min(width - px).min(py).min(height - py)
+                }
+            }
+            Self::Circle { x, y, radius } => {
+                let (dx, dy) = (px - x as f32, py - y as f32);
+
+                radius as f32 - (dx * dx + dy * dy).sqrt()
+            }
+            Self::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                width,
+            } => {
+                let (ax, ay) = (x1 as f32, y1 as f32);
+                let (bx, by) = (x2 as f32 - ax, y2 as f32 - ay);
+                let length = bx * bx + by * by;
+                let t = if length == 0. {
+                    0.
+                } else {
+                    (((px - ax) * bx + (py - ay) * by) / length).clamp(0., 1.)
+                };
+                let (dx, dy) = (px - ax - bx * t, py - ay - by * t);
+
+                width as f32 / 2. - (dx * dx + dy * dy).sqrt()
+            }
+        }
+    }
+}
+
+/// A color at a position of a gradient.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColorStop {
+    /// The position of the color between `0.0` (start) and `1.0` (end) of the gradient.
+    pub offset: f32,
+    /// The color at the position.
+    pub color: Color,
+}
+
+/// Describes how the inside of a shape is colored.
+///
+/// Gradients span the bounding box of the shape they fill.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fill {
+    /// A single color.
+    Solid {
+        /// The color of the shape.
+        color: Color,
+    },
+    /// Colors changing along a straight line.
+    LinearGradient {
+        /// The direction of the gradient in degrees. `0` runs from left to right,
+        /// `90` from top to bottom.
+        #[serde(default)]
+        angle: f32,
+        /// The colors of the gradient.
+        stops: Vec<ColorStop>,
+    },
+    /// Colors changing from the center of the shape outwards.
+    RadialGradient {
+        /// The colors of the gradient.
+        stops: Vec<ColorStop>,
+    },
+}
+
+impl Fill {
+    /// Returns the color at a point of a bounding box.
+    ///
+    /// # Arguments
+    /// * `u` - The horizontal position in the bounding box, from `0.0` to `1.0`.
+    /// * `v` - The vertical position in the bounding box, from `0.0` to `1.0`.
+    /// * `aspect` - The width of the bounding box divided by its height.
+    fn color_at(&self, u: f32, v: f32, aspect: f32) -> Rgba<u8> {
+        match self {
+            Self::Solid { color } => color.0,
+            Self::LinearGradient { angle, stops } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let (dx, dy) = ((u - 0.5) * aspect, v - 0.5);
+                let half_length = (cos.abs() * aspect + sin.abs()) / 2.;
+
+                gradient_color(stops, (dx * cos + dy * sin) / (half_length * 2.) + 0.5)
+            }
+            Self::RadialGradient { stops } => {
+                let (dx, dy) = (u - 0.5, v - 0.5);
+
+                gradient_color(stops, (dx * dx + dy * dy).sqrt() * 2.)
+            }
+        }
+    }
+}
+
+/// Interpolates the color of a gradient at a position.
+///
+/// # Arguments
+/// * `stops` - The colors of the gradient, in any order.
+/// * `t` - The position in the gradient. It is clamped to the first and last stop.
+fn gradient_color(stops: &[ColorStop], t: f32) -> Rgba<u8> {
+    let mut previous: Option<&ColorStop> = None;
+    let mut sorted = stops.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+
+    for stop in sorted {
+        if t <= stop.offset {
+            let Some(previous) = previous else {
+                return stop.color.0;
+            };
+
+            let span = stop.offset - previous.offset;
+            let weight = if span > 0. {
+                (t - previous.offset) / span
+            } else {
+                1.
+            };
+
+            return interpolate(previous.color.0, stop.color.0, weight);
+        }
+
+        previous = Some(stop);
+    }
+
+    previous.map_or(Rgba([0, 0, 0, 0]), |stop| stop.color.0)
+}
+
+/// Interpolates between two colors with premultiplied alpha, so fading into a
+/// transparent color does not darken or tint the result.
+///
+/// # Arguments
+/// * `start` - The color at `weight` `0.0`.
+/// * `end` - The color at `weight` `1.0`.
+/// * `weight` - The position between both colors.
+fn interpolate(start: Rgba<u8>, end: Rgba<u8>, weight: f32) -> Rgba<u8> {
+    let start_alpha = f32::from(start[3]) / 255.;
+    let end_alpha = f32::from(end[3]) / 255.;
+    let alpha = start_alpha + (end_alpha - start_alpha) * weight;
+
+    if alpha <= 0. {
+        return Rgba([0, 0, 0, 0]);
+    }
+
+    let channel = |index: usize| {
+        let start = f32::from(start[index]) * start_alpha;
+        let end = f32::from(end[index]) * end_alpha;
+
+        ((start + (end - start) * weight) / alpha)
+            .round()
+            .clamp(0., 255.) as u8
+    };
+
+    Rgba([
+        channel(0),
+        channel(1),
+        channel(2),
+        (alpha * 255.).round() as u8,
+    ])
+}
+
+/// Draws a filled shape onto an image.
+///
+/// # Arguments
+/// * `base_image` - The image to draw onto.
+/// * `shape` - The shape to draw.
+/// * `fill` - The colors of the shape.
+/// * `opacity` - The opacity of the whole shape, from `0.0` to `1.0`.
+#[fastrace::trace]
+pub(crate) fn draw_shape(base_image: &mut DynamicImage, shape: &Shape, fill: &Fill, opacity: f32) {
+    let opacity = opacity.clamp(0., 1.);
+    let (left, top, width, height) = shape.bounds();
+
+    if opacity == 0. || width <= 0. || height <= 0. {
+        return;
+    }
+
+    let aspect = width / height;
+    let x_range = (left.floor().max(0.) as u32)..((left + width).ceil().max(0.) as u32);
+    let y_range = (top.floor().max(0.) as u32)..((top + height).ceil().max(0.) as u32);
+
+    for y in y_range {
+        for x in x_range.clone() {
+            if !base_image.in_bounds(x, y) {
+                continue;
+            }
+
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let coverage = (shape.distance(px, py) + 0.5).clamp(0., 1.);
+
+            if coverage == 0. {
+                continue;
+            }
+
+            let mut color = fill.color_at((px - left) / width, (py - top) / height, aspect);
+            color[3] = (f32::from(color[3]) * coverage * opacity).round() as u8;
+
+            let mut pixel = base_image.get_pixel(x, y);
+            pixel.blend(&color);
+            base_image.put_pixel(x, y, pixel);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use image::DynamicImage;
+
+use crate::{
+    animation::{self, FrameSequence},
+    cache::AssetCache,
+    error::Error,
+};
+
+/// Describes where the pixels of an image come from.
+///
+/// Files are decoded once and kept in the asset cache of the [`ImageGenerator`],
+/// while encoded buffers, decoded images and decoded frames are used without touching
+/// the disk.
+///
+/// [`ImageGenerator`]: crate::ImageGenerator
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageSource {
+    /// An image file on disk.
+    Path(PathBuf),
+    /// An encoded image, e.g. a PNG or an animated GIF downloaded from a CDN.
+    Bytes(Arc<[u8]>),
+    /// An already decoded image.
+    Image(Arc<DynamicImage>),
+    /// The already decoded frames of a possibly animated image.
+    Frames(Arc<FrameSequence>),
+}
+
+impl ImageSource {
+    /// Returns the image, using the first frame of animated images.
+    ///
+    /// # Arguments
+    /// * `cache` - The cache of decoded files.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the image cannot be read or decoded.
+    pub(crate) fn load_still(&self, cache: &AssetCache) -> Result<DynamicImage, Error> {
+        match self {
+            Self::Path(path) => cache.first_frame(path),
+            Self::Bytes(bytes) => Ok(image::load_from_memory(bytes)?),
+            Self::Image(image) => Ok(image.as_ref().clone()),
+            Self::Frames(frames) => Ok(frames.first_frame().clone()),
+        }
+    }
+
+    /// Checks whether the image has more than one frame, without decoding every frame.
+    ///
+    /// # Arguments
+    /// * `cache` - The cache of decoded files.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the image cannot be read or decoded.
+    pub(crate) fn is_animated(&self, cache: &AssetCache) -> Result<bool, Error> {
+        match self {
+            Self::Path(path) => cache.is_animated(path),
+            Self::Bytes(bytes) => Ok(animation::is_animated(bytes)),
+            Self::Image(_) => Ok(false),
+            Self::Frames(frames) => Ok(frames.is_animated()),
+        }
+    }
+
+    /// Returns every frame of the image.
+    ///
+    /// # Arguments
+    /// * `cache` - The cache of decoded files.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the image cannot be read or decoded.
+    pub(crate) fn load_frames(&self, cache: &AssetCache) -> Result<Arc<FrameSequence>, Error> {
+        match self {
+            Self::Path(path) => cache.get(path),
+            Self::Bytes(bytes) => Ok(Arc::new(FrameSequence::decode(bytes)?)),
+            Self::Image(image) => Ok(Arc::new(FrameSequence::still(image.as_ref().clone()))),
+            Self::Frames(frames) => Ok(frames.clone()),
+        }
+    }
+}
+
+impl From<PathBuf> for ImageSource {
+    /// Creates a source reading an image file.
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&PathBuf> for ImageSource {
+    /// Creates a source reading an image file.
+    fn from(path: &PathBuf) -> Self {
+        Self::Path(path.clone())
+    }
+}
+
+impl From<&Path> for ImageSource {
+    /// Creates a source reading an image file.
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+impl From<&str> for ImageSource {
+    /// Creates a source reading an image file.
+    fn from(path: &str) -> Self {
+        Self::Path(PathBuf::from(path))
+    }
+}
+
+impl From<String> for ImageSource {
+    /// Creates a source reading an image file.
+    fn from(path: String) -> Self {
+        Self::Path(PathBuf::from(path))
+    }
+}
+
+impl From<Vec<u8>> for ImageSource {
+    /// Creates a source decoding an encoded image.
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::Bytes(bytes.into())
+    }
+}
+
+impl From<DynamicImage> for ImageSource {
+    /// Creates a source using a decoded image.
+    fn from(image: DynamicImage) -> Self {
+        Self::Image(Arc::new(image))
+    }
+}
+
+impl From<FrameSequence> for ImageSource {
+    /// Creates a source using decoded frames.
+    fn from(frames: FrameSequence) -> Self {
+        Self::Frames(Arc::new(frames))
+    }
+}
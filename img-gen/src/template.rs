@@ -0,0 +1,345 @@
+use std::path::PathBuf;
+
+use ab_glyph::PxScale;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color::Color,
+    effects::TextEffects,
+    error::Error,
+    mask::Mask,
+    picture::PictureStyle,
+    shape::{Fill, Shape},
+    source::ImageSource,
+    text::{HorizontalAlign, TextBox, VerticalAlign},
+    text_template::Variables,
+    ImageBuilder,
+};
+
+/// Describes where the pixels of an image layer come from.
+///
+/// The `background`, `foreground` and `avatar` sources are resolved at render time
+/// through [`TemplateAssets`], so the same template can be reused for every member.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetSource {
+    /// The background banner configured for the guild.
+    Background,
+    /// The foreground banner configured for the guild.
+    Foreground,
+    /// The avatar of the member the card is rendered for.
+    Avatar,
+    /// A fixed image on disk.
+    Path(PathBuf),
+}
+
+/// A single layer of a template, drawn in the order it appears.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Layer {
+    /// An image drawn at the given position.
+    Image {
+        /// Where the image is loaded from.
+        source: AssetSource,
+        /// The x-coordinate of the overlay position.
+        x: i64,
+        /// The y-coordinate of the overlay position.
+        y: i64,
+        /// An optional mask to cut out the image, e.g. to draw round avatars.
+        #[serde(default)]
+        mask: Option<Mask>,
+        /// The size, rotation, opacity and blend mode of the image.
+        #[serde(flatten)]
+        style: PictureStyle,
+    },
+    /// A line of text. Placeholders in `{braces}` are filled in at render time.
+    Text {
+        /// The text content, which may contain placeholders.
+        text: String,
+        /// The x-coordinate of the text position.
+        x: i32,
+        /// The y-coordinate of the text position.
+        y: i32,
+        /// The font size in pixels.
+        scale: f32,
+        /// The name of the font registered in the generator.
+        font: String,
+        /// The color of the text.
+        color: Color,
+        /// Whether to center the text around the position.
+        #[serde(default)]
+        center: bool,
+        /// The stroke, shadow and glow drawn behind the text.
+        #[serde(default)]
+        effects: TextEffects,
+    },
+    /// Text fitted into a box. It is wrapped, shrunk and cut off with an ellipsis
+    /// until it fits. Placeholders in `{braces}` are filled in at render time.
+    TextBox {
+        /// The text content, which may contain placeholders.
+        text: String,
+        /// The x-coordinate of the left edge of the box.
+        x: i32,
+        /// The y-coordinate of the top edge of the box.
+        y: i32,
+        /// The width of the box in pixels.
+        width: u32,
+        /// The height of the box in pixels.
+        height: u32,
+        /// The font size used if the text fits.
+        max_scale: f32,
+        /// The smallest font size the text may be shrunk to.
+        min_scale: f32,
+        /// The name of the font registered in the generator.
+        font: String,
+        /// The color of the text.
+        color: Color,
+        /// The horizontal alignment of the lines.
+        #[serde(default)]
+        align: HorizontalAlign,
+        /// The vertical alignment of the text block.
+        #[serde(default)]
+        vertical_align: VerticalAlign,
+        /// The stroke, shadow and glow drawn behind the text.
+        #[serde(default)]
+        effects: TextEffects,
+    },
+    /// A filled rectangle, rounded rectangle, circle or line.
+    Shape {
+        /// The geometry of the shape, selected by its `shape` key.
+        #[serde(flatten)]
+        shape: Shape,
+        /// The colors of the shape.
+        fill: Fill,
+        /// The opacity of the whole shape, from `0.0` to `1.0`.
+        #[serde(default = "default_opacity")]
+        opacity: f32,
+    },
+}
+
+/// A serializable description of a welcome card.
+///
+/// Templates can be written as TOML or JSON and turned into an [`ImageBuilder`]
+/// with [`Template::build`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    /// The image every layer is drawn onto.
+    #[serde(default = "default_base")]
+    pub base: AssetSource,
+    /// The layers drawn on top of the base image.
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+}
+
+/// The default opacity of shape layers.
+const fn default_opacity() -> f32 {
+    1.
+}
+
+/// The default base image of a template.
+const fn default_base() -> AssetSource {
+    AssetSource::Background
+}
+
+/// The concrete images used to resolve the asset sources of a template.
+#[derive(Debug, Clone)]
+pub struct TemplateAssets {
+    /// The background banner.
+    pub background: ImageSource,
+    /// The foreground banner.
+    pub foreground: ImageSource,
+    /// The avatar of the member.
+    pub avatar: ImageSource,
+}
+
+impl TemplateAssets {
+    /// Resolves an asset source to an image source.
+    ///
+    /// # Arguments
+    /// * `source` - The source to resolve.
+    fn resolve(&self, source: &AssetSource) -> ImageSource {
+        match source {
+            AssetSource::Background => self.background.clone(),
+            AssetSource::Foreground => self.foreground.clone(),
+            AssetSource::Avatar => self.avatar.clone(),
+            AssetSource::Path(path) => ImageSource::Path(path.clone()),
+        }
+    }
+}
+
+impl Template {
+    /// Parses a template from a TOML document.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the document is not a valid template.
+    pub fn from_toml(value: &str) -> Result<Self, Error> {
+        Ok(toml::from_str(value)?)
+    }
+
+    /// Parses a template from a JSON document.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the document is not a valid template.
+    pub fn from_json(value: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(value)?)
+    }
+
+    /// Parses a template from either a JSON or a TOML document.
+    ///
+    /// Documents starting with `{` are treated as JSON, everything else as TOML.
+    ///
+    /// # Errors
+    /// Returns an [`Error`] if the document is not a valid template.
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        if value.trim_start().starts_with('{') {
+            Self::from_json(value)
+        } else {
+            Self::from_toml(value)
+        }
+    }
+
+    /// Creates an `ImageBuilder` from the template.
+    ///
+    /// # Arguments
+    /// * `assets` - The images used for the background, foreground and avatar sources.
+    /// * `variables` - The values of the placeholders in text layers. The texts are
+    ///   rendered as [`TextTemplate`]s, so they may use filters and conditionals.
+    ///
+    /// [`TextTemplate`]: crate::text_template::TextTemplate
+    #[must_use]
+    pub fn build(&self, assets: &TemplateAssets, variables: &Variables) -> ImageBuilder {
+        self.build_with(assets, |text| variables.render(text))
+    }
+
+    /// Creates an `ImageBuilder` from the template, filling in text layers with a
+    /// custom function.
+    ///
+    /// # Arguments
+    /// * `assets` - The images used for the background, foreground and avatar sources.
+    /// * `fill_text` - Turns the text of a layer into the text drawn.
+    #[must_use]
+    pub fn build_with(
+        &self,
+        assets: &TemplateAssets,
+        fill_text: impl Fn(&str) -> String,
+    ) -> ImageBuilder {
+        let mut builder = ImageBuilder::new(assets.resolve(&self.base));
+
+        for layer in &self.layers {
+            builder = match layer {
+                Layer::Image {
+                    source,
+                    x,
+                    y,
+                    mask: Some(mask),
+                    style,
+                } => builder
+                    .add_masked_image(assets.resolve(source), *x, *y, mask.clone())
+                    .with_picture_style(*style),
+                Layer::Image {
+                    source,
+                    x,
+                    y,
+                    mask: None,
+                    style,
+                } => builder
+                    .add_image(assets.resolve(source), *x, *y)
+                    .with_picture_style(*style),
+                Layer::Text {
+                    text,
+                    x,
+                    y,
+                    scale,
+                    font,
+                    color,
+                    center,
+                    effects,
+                } => builder
+                    .add_text(
+                        &fill_text(text),
+                        *x,
+                        *y,
+                        PxScale::from(*scale),
+                        font,
+                        (*color).into(),
+                        *center,
+                    )
+                    .with_text_effects(*effects),
+                Layer::TextBox {
+                    text,
+                    x,
+                    y,
+                    width,
+                    height,
+                    max_scale,
+                    min_scale,
+                    font,
+                    color,
+                    align,
+                    vertical_align,
+                    effects,
+                } => builder
+                    .add_text_box(
+                        &fill_text(text),
+                        TextBox {
+                            x: *x,
+                            y: *y,
+                            width: *width,
+                            height: *height,
+                            max_scale: *max_scale,
+                            min_scale: *min_scale,
+                            align: *align,
+                            vertical_align: *vertical_align,
+                        },
+                        font,
+                        (*color).into(),
+                    )
+                    .with_text_effects(*effects),
+                Layer::Shape {
+                    shape,
+                    fill,
+                    opacity,
+                } => builder.add_shape(*shape, fill.clone(), *opacity),
+            };
+        }
+
+        builder
+    }
+
+    /// Returns the names of all fonts used by the template.
+    pub fn fonts(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().filter_map(|layer| match layer {
+            Layer::Text { font, .. } | Layer::TextBox { font, .. } => Some(font.as_str()),
+            Layer::Image { .. } | Layer::Shape { .. } => None,
+        })
+    }
+
+    /// Returns the texts of every text layer, before placeholders are filled in.
+    pub fn texts(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().filter_map(|layer| match layer {
+            Layer::Text { text, .. } | Layer::TextBox { text, .. } => Some(text.as_str()),
+            Layer::Image { .. } | Layer::Shape { .. } => None,
+        })
+    }
+
+    /// Checks whether the template reads any fixed file from disk.
+    ///
+    /// Templates provided by users should only use the background, foreground and avatar
+    /// sources, so they cannot read arbitrary files of the host.
+    #[must_use]
+    pub fn references_files(&self) -> bool {
+        matches!(self.base, AssetSource::Path(_))
+            || self.layers.iter().any(|layer| {
+                matches!(
+                    layer,
+                    Layer::Image {
+                        source: AssetSource::Path(_),
+                        ..
+                    } | Layer::Image {
+                        mask: Some(Mask::Image { .. }),
+                        ..
+                    }
+                )
+            })
+    }
+}
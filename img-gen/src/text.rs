@@ -0,0 +1,199 @@
+use ab_glyph::PxScale;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::font::FontStack;
+
+/// The character appended to text which had to be cut off.
+const ELLIPSIS: &str = "…";
+
+/// The horizontal alignment of the lines in a text box.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HorizontalAlign {
+    /// Lines start at the left edge of the box.
+    Left,
+    /// Lines are centered in the box.
+    #[default]
+    Center,
+    /// Lines end at the right edge of the box.
+    Right,
+}
+
+/// The vertical alignment of the text block in a text box.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerticalAlign {
+    /// The text starts at the top edge of the box.
+    #[default]
+    Top,
+    /// The text is centered in the box.
+    Middle,
+    /// The text ends at the bottom edge of the box.
+    Bottom,
+}
+
+/// The area text is fitted into.
+///
+/// The text is wrapped across lines and shrunk from `max_scale` down to `min_scale`
+/// until it fits. Text that does not even fit at `min_scale` is cut off with an ellipsis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextBox {
+    /// The x-coordinate of the left edge of the box.
+    pub x: i32,
+    /// The y-coordinate of the top edge of the box.
+    pub y: i32,
+    /// The width of the box in pixels.
+    pub width: u32,
+    /// The height of the box in pixels.
+    pub height: u32,
+    /// The font size used if the text fits.
+    pub max_scale: f32,
+    /// The smallest font size the text may be shrunk to.
+    pub min_scale: f32,
+    /// The horizontal alignment of the lines.
+    pub align: HorizontalAlign,
+    /// The vertical alignment of the text block.
+    pub vertical_align: VerticalAlign,
+}
+
+/// The result of fitting text into a [`TextBox`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TextLayout {
+    /// The font size the text is drawn with.
+    pub scale: PxScale,
+    /// The lines together with their position relative to the image.
+    pub lines: Vec<(String, i32, i32)>,
+}
+
+impl TextBox {
+    /// Fits text into the box.
+    ///
+    /// # Arguments
+    /// * `fonts` - The fonts the text is drawn with.
+    /// * `text` - The text to fit.
+    pub(crate) fn layout(&self, fonts: &FontStack, text: &str) -> TextLayout {
+        let min_scale = self.min_scale.min(self.max_scale).max(1.);
+        let mut scale = self.max_scale.max(min_scale);
+
+        let (scale, lines) = loop {
+            let px_scale = PxScale::from(scale);
+            let lines = wrap(fonts, px_scale, text, self.width);
+
+            if lines.len() <= self.max_lines(fonts, px_scale) || scale <= min_scale {
+                break (px_scale, lines);
+            }
+
+            scale = (scale - 1.).max(min_scale);
+        };
+
+        let max_lines = self.max_lines(fonts, scale);
+        let lines = if lines.len() > max_lines {
+            let mut lines = lines;
+            lines.truncate(max_lines);
+
+            if let Some(last) = lines.last_mut() {
+                *last = ellipsize(fonts, scale, last, self.width);
+            }
+
+            lines
+        } else {
+            lines
+        };
+
+        let line_height = fonts.line_height(scale);
+        let block_height = line_height * lines.len() as f32;
+        let top = match self.vertical_align {
+            VerticalAlign::Top => 0.,
+            VerticalAlign::Middle => (self.height as f32 - block_height) / 2.,
+            VerticalAlign::Bottom => self.height as f32 - block_height,
+        };
+
+        let lines = lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let line_width = fonts.text_width(scale, &line);
+                let left = match self.align {
+                    HorizontalAlign::Left => 0,
+                    HorizontalAlign::Center => (self.width as i32 - line_width as i32) / 2,
+                    HorizontalAlign::Right => self.width as i32 - line_width as i32,
+                };
+                let y = self.y + (top + line_height * index as f32).round() as i32;
+
+                (line, self.x + left, y)
+            })
+            .collect();
+
+        TextLayout { scale, lines }
+    }
+
+    /// Returns how many lines fit into the box. At least one line is always allowed.
+    fn max_lines(&self, fonts: &FontStack, scale: PxScale) -> usize {
+        ((self.height as f32 / fonts.line_height(scale)).floor() as usize).max(1)
+    }
+}
+
+/// Wraps text into lines that are at most `max_width` pixels wide.
+///
+/// Lines are broken at whitespace and explicit newlines. Words which are wider
+/// than a whole line are broken between grapheme clusters, so emoji sequences and
+/// combining marks stay together.
+fn wrap(fonts: &FontStack, scale: PxScale, text: &str, max_width: u32) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{line} {word}")
+            };
+
+            if fonts.text_width(scale, &candidate) <= max_width {
+                line = candidate;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+
+            for grapheme in word.graphemes(true) {
+                let start = line.len();
+                line.push_str(grapheme);
+
+                if start > 0 && fonts.text_width(scale, &line) > max_width {
+                    line.truncate(start);
+                    lines.push(std::mem::replace(&mut line, grapheme.to_string()));
+                }
+            }
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Shortens a line and appends an ellipsis so it fits into `max_width` pixels.
+fn ellipsize(fonts: &FontStack, scale: PxScale, line: &str, max_width: u32) -> String {
+    let mut line = line.trim_end().to_string();
+
+    loop {
+        let candidate = format!("{line}{ELLIPSIS}");
+
+        if line.is_empty() || fonts.text_width(scale, &candidate) <= max_width {
+            return candidate;
+        }
+
+        let last = line
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i);
+        line.truncate(last);
+        line.truncate(line.trim_end().len());
+    }
+}
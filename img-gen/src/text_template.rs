@@ -0,0 +1,709 @@
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+use log::warn;
+
+/// Describes why a text template could not be parsed.
+///
+/// Positions count characters from `1`, so they can be shown to users directly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextTemplateError {
+    /// A `{` has no matching `}`.
+    UnclosedPlaceholder(usize),
+
+    /// A placeholder names a variable which does not exist.
+    UnknownVariable {
+        /// The name of the variable.
+        name: String,
+        /// The position of the placeholder.
+        position: usize,
+    },
+
+    /// A placeholder uses a filter which does not exist or has an invalid argument.
+    InvalidFilter {
+        /// The filter as written.
+        name: String,
+        /// The position of the placeholder.
+        position: usize,
+    },
+
+    /// An `{if}` has a condition which cannot be parsed.
+    InvalidCondition {
+        /// The condition as written.
+        condition: String,
+        /// The position of the `{if}`.
+        position: usize,
+    },
+
+    /// An `{else}` or `{end}` appears without an open `{if}`.
+    UnexpectedTag {
+        /// The tag, `else` or `end`.
+        tag: String,
+        /// The position of the tag.
+        position: usize,
+    },
+
+    /// An `{if}` is never closed with `{end}`.
+    UnclosedIf(usize),
+}
+
+impl fmt::Display for TextTemplateError {
+    /// Formats the error as a message which can be shown to users.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnclosedPlaceholder(position) => write!(
+                f,
+                "The placeholder at position {position} is missing its closing `}}`."
+            ),
+            Self::UnknownVariable { name, position } => {
+                write!(f, "Unknown placeholder `{name}` at position {position}.")
+            }
+            Self::InvalidFilter { name, position } => write!(
+                f,
+                "Unknown or invalid filter `{name}` at position {position}."
+            ),
+            Self::InvalidCondition {
+                condition,
+                position,
+            } => write!(f, "Invalid condition `{condition}` at position {position}."),
+            Self::UnexpectedTag { tag, position } => write!(
+                f,
+                "`{{{tag}}}` at position {position} has no matching `{{if}}`."
+            ),
+            Self::UnclosedIf(position) => write!(
+                f,
+                "The `{{if}}` at position {position} is missing its `{{end}}`."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TextTemplateError {}
+
+/// The values inserted for the placeholders of a text template.
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    /// The values by variable name.
+    values: HashMap<String, String>,
+}
+
+impl Variables {
+    /// Sets the value of a variable.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the variable.
+    /// * `value` - The value inserted for the variable.
+    pub fn insert(&mut self, name: &str, value: impl Into<String>) -> &mut Self {
+        self.values.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Returns the value of a variable, or an empty text if it is not set.
+    fn get(&self, name: &str) -> &str {
+        self.values.get(name).map_or("", String::as_str)
+    }
+
+    /// Parses and renders a text, keeping it unchanged if it is not a valid template.
+    ///
+    /// Texts are validated when they are saved, so invalid texts only exist if they
+    /// were stored before the template engine existed.
+    ///
+    /// # Arguments
+    /// * `text` - The text containing placeholders.
+    #[must_use]
+    pub fn render(&self, text: &str) -> String {
+        let names = self.values.keys().map(String::as_str).collect::<Vec<_>>();
+
+        match TextTemplate::parse(text, &names) {
+            Ok(template) => template.render(self),
+            Err(why) => {
+                warn!("Could not render '{text}': {why}");
+                text.to_string()
+            }
+        }
+    }
+}
+
+/// A change applied to the value of a placeholder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    /// Converts the value to upper case.
+    Upper,
+    /// Converts the value to lower case.
+    Lower,
+    /// Converts the first character to upper case.
+    Capitalize,
+    /// Appends the English ordinal suffix to a number, e.g. `42nd`.
+    Ordinal,
+    /// Shortens the value to a number of characters, ending it with an ellipsis.
+    Truncate(usize),
+}
+
+impl Filter {
+    /// Parses a filter like `upper` or `truncate:20`.
+    fn parse(filter: &str) -> Option<Self> {
+        match filter.split_once(':') {
+            Some(("truncate", length)) => length.trim().parse().ok().map(Self::Truncate),
+            Some(_) => None,
+            None => match filter {
+                "upper" => Some(Self::Upper),
+                "lower" => Some(Self::Lower),
+                "capitalize" => Some(Self::Capitalize),
+                "ordinal" => Some(Self::Ordinal),
+                _ => None,
+            },
+        }
+    }
+
+    /// Applies the filter to a value.
+    fn apply(&self, value: String) -> String {
+        match self {
+            Self::Upper => value.to_uppercase(),
+            Self::Lower => value.to_lowercase(),
+            Self::Capitalize => {
+                let mut chars = value.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_uppercase().chain(chars).collect()
+                })
+            }
+            Self::Ordinal => match value.parse::<u64>() {
+                Ok(number) => format!("{number}{}", ordinal_suffix(number)),
+                Err(_) => value,
+            },
+            Self::Truncate(length) => {
+                if value.chars().count() <= *length {
+                    value
+                } else {
+                    let mut truncated = value
+                        .chars()
+                        .take(length.saturating_sub(1))
+                        .collect::<String>();
+                    truncated.push('…');
+                    truncated
+                }
+            }
+        }
+    }
+}
+
+/// A value a variable is compared with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Literal {
+    /// A whole number. Variables holding numbers are compared numerically.
+    Number(i64),
+    /// A text in double quotes.
+    Text(String),
+}
+
+/// The condition of an `{if}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Condition {
+    /// Whether the result is inverted with `not`.
+    negate: bool,
+    /// The variable the condition checks.
+    variable: String,
+    /// The comparison, or `None` to check whether the variable is set.
+    comparison: Option<(Ordering, bool, Literal)>,
+}
+
+impl Condition {
+    /// Parses a condition like `members >= 100`, `not mention` or `name == "Ferris"`.
+    ///
+    /// # Returns
+    /// Returns the condition, or `None` if it cannot be parsed.
+    fn parse(condition: &str) -> Option<Self> {
+        let (negate, condition) = match condition.strip_prefix("not ") {
+            Some(condition) => (true, condition.trim_start()),
+            None => (false, condition),
+        };
+
+        let end = condition
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(condition.len());
+        let (variable, rest) = condition.split_at(end);
+        let rest = rest.trim();
+
+        if variable.is_empty() {
+            return None;
+        }
+
+        if rest.is_empty() {
+            return Some(Self {
+                negate,
+                variable: variable.to_string(),
+                comparison: None,
+            });
+        }
+
+        // The ordering the comparison matches, and whether it is inverted.
+        let operators = [
+            (">=", Ordering::Less, true),
+            ("<=", Ordering::Greater, true),
+            ("==", Ordering::Equal, false),
+            ("!=", Ordering::Equal, true),
+            (">", Ordering::Greater, false),
+            ("<", Ordering::Less, false),
+        ];
+
+        let (literal, ordering, inverted) =
+            operators
+                .iter()
+                .find_map(|(operator, ordering, inverted)| {
+                    rest.strip_prefix(operator)
+                        .map(|literal| (literal.trim(), *ordering, *inverted))
+                })?;
+
+        let literal = if let Some(text) = literal
+            .strip_prefix('"')
+            .and_then(|literal| literal.strip_suffix('"'))
+        {
+            Literal::Text(text.to_string())
+        } else {
+            Literal::Number(literal.parse().ok()?)
+        };
+
+        Some(Self {
+            negate,
+            variable: variable.to_string(),
+            comparison: Some((ordering, inverted, literal)),
+        })
+    }
+
+    /// Evaluates the condition.
+    fn evaluate(&self, variables: &Variables) -> bool {
+        let value = variables.get(&self.variable);
+
+        let result = match &self.comparison {
+            None => !value.is_empty() && value != "0",
+            Some((ordering, inverted, literal)) => {
+                let actual = match (literal, value.parse::<i64>()) {
+                    (Literal::Number(number), Ok(value)) => value.cmp(number),
+                    (Literal::Number(number), Err(_)) => value.cmp(number.to_string().as_str()),
+                    (Literal::Text(text), _) => value.cmp(text.as_str()),
+                };
+
+                (actual == *ordering) != *inverted
+            }
+        };
+
+        result != self.negate
+    }
+}
+
+/// A part of a parsed text template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    /// Text inserted as it is.
+    Text(String),
+    /// The value of a variable, changed by filters.
+    Variable {
+        /// The name of the variable.
+        name: String,
+        /// The filters applied in order.
+        filters: Vec<Filter>,
+    },
+    /// Parts inserted depending on a condition.
+    If {
+        /// The condition deciding which branch is inserted.
+        condition: Condition,
+        /// The parts inserted if the condition is true.
+        then: Vec<Node>,
+        /// The parts inserted if the condition is false.
+        otherwise: Vec<Node>,
+    },
+}
+
+/// An `{if}` whose `{end}` has not been parsed yet.
+struct OpenIf {
+    /// The position of the `{if}`.
+    position: usize,
+    /// The condition of the `{if}`.
+    condition: Condition,
+    /// The parts before the `{else}`, once it has been parsed.
+    then: Option<Vec<Node>>,
+    /// The parts parsed before the `{if}`.
+    parent: Vec<Node>,
+}
+
+/// A parsed text with placeholders, shared by every welcome and goodbye text.
+///
+/// * `{name}` inserts a variable, `{name|upper}` applies filters to it. The filters
+///   are `upper`, `lower`, `capitalize`, `ordinal` and `truncate:N`.
+/// * `{if members >= 1000}...{else}...{end}` inserts text depending on a condition.
+///   Conditions check whether a variable is set, or compare it with a number or a
+///   text in double quotes using `==`, `!=`, `<`, `<=`, `>` or `>=`. `not` inverts them.
+/// * `{{` and `}}` insert literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextTemplate {
+    /// The parsed parts of the text.
+    nodes: Vec<Node>,
+}
+
+impl TextTemplate {
+    /// Parses a text template.
+    ///
+    /// # Arguments
+    /// * `text` - The text containing placeholders.
+    /// * `variables` - The names of the variables the text may use.
+    ///
+    /// # Errors
+    /// Returns a [`TextTemplateError`] if the text is not a valid template.
+    pub fn parse(text: &str, variables: &[&str]) -> Result<Self, TextTemplateError> {
+        let mut nodes = Vec::new();
+        let mut open_ifs: Vec<OpenIf> = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars().enumerate().peekable();
+
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|(_, c)| *c == '{').is_some() => literal.push('{'),
+                '}' if chars.next_if(|(_, c)| *c == '}').is_some() => literal.push('}'),
+                '{' => {
+                    let position = index + 1;
+                    let mut tag = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, '{')) | None => {
+                                return Err(TextTemplateError::UnclosedPlaceholder(position));
+                            }
+                            Some((_, c)) => tag.push(c),
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut literal)));
+                    }
+
+                    parse_tag(tag.trim(), position, variables, &mut nodes, &mut open_ifs)?;
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if let Some(open_if) = open_ifs.last() {
+            return Err(TextTemplateError::UnclosedIf(open_if.position));
+        }
+
+        if !literal.is_empty() {
+            nodes.push(Node::Text(literal));
+        }
+
+        Ok(Self { nodes })
+    }
+
+    /// Renders the template.
+    ///
+    /// # Arguments
+    /// * `variables` - The values inserted for the placeholders.
+    #[must_use]
+    pub fn render(&self, variables: &Variables) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, variables, &mut output);
+        output
+    }
+}
+
+/// Parses the content of a `{...}` tag and adds it to the parsed parts.
+///
+/// # Arguments
+/// * `tag` - The content of the tag without braces.
+/// * `position` - The position of the tag.
+/// * `variables` - The names of the variables the text may use.
+/// * `nodes` - The parts of the innermost open `{if}` branch, or of the whole text.
+/// * `open_ifs` - The `{if}`s which have not been closed yet.
+///
+/// # Errors
+/// Returns a [`TextTemplateError`] if the tag is invalid.
+fn parse_tag(
+    tag: &str,
+    position: usize,
+    variables: &[&str],
+    nodes: &mut Vec<Node>,
+    open_ifs: &mut Vec<OpenIf>,
+) -> Result<(), TextTemplateError> {
+    let unexpected = |tag: &str| TextTemplateError::UnexpectedTag {
+        tag: tag.to_string(),
+        position,
+    };
+
+    if let Some(condition) = tag
+        .strip_prefix("if")
+        .filter(|condition| condition.is_empty() || condition.starts_with(char::is_whitespace))
+    {
+        let condition = condition.trim();
+        let invalid = || TextTemplateError::InvalidCondition {
+            condition: condition.to_string(),
+            position,
+        };
+
+        let condition = Condition::parse(condition).ok_or_else(invalid)?;
+        check_variable(&condition.variable, position, variables)?;
+
+        open_ifs.push(OpenIf {
+            position,
+            condition,
+            then: None,
+            parent: std::mem::take(nodes),
+        });
+    } else if tag == "else" {
+        let open_if = open_ifs
+            .last_mut()
+            .filter(|open_if| open_if.then.is_none())
+            .ok_or_else(|| unexpected(tag))?;
+
+        open_if.then = Some(std::mem::take(nodes));
+    } else if tag == "end" {
+        let open_if = open_ifs.pop().ok_or_else(|| unexpected(tag))?;
+        let branch = std::mem::replace(nodes, open_if.parent);
+
+        let (then, otherwise) = match open_if.then {
+            Some(then) => (then, branch),
+            None => (branch, Vec::new()),
+        };
+
+        nodes.push(Node::If {
+            condition: open_if.condition,
+            then,
+            otherwise,
+        });
+    } else {
+        let mut parts = tag.split('|').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        check_variable(name, position, variables)?;
+
+        let filters = parts
+            .map(|filter| {
+                Filter::parse(filter).ok_or_else(|| TextTemplateError::InvalidFilter {
+                    name: filter.to_string(),
+                    position,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        nodes.push(Node::Variable {
+            name: name.to_string(),
+            filters,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that a variable exists.
+///
+/// # Errors
+/// Returns a [`TextTemplateError`] if the variable is not one of `variables`.
+fn check_variable(
+    name: &str,
+    position: usize,
+    variables: &[&str],
+) -> Result<(), TextTemplateError> {
+    if variables.contains(&name) {
+        Ok(())
+    } else {
+        Err(TextTemplateError::UnknownVariable {
+            name: name.to_string(),
+            position,
+        })
+    }
+}
+
+/// Renders parsed parts into a text.
+fn render_nodes(nodes: &[Node], variables: &Variables, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Variable { name, filters } => {
+                let value = filters
+                    .iter()
+                    .fold(variables.get(name).to_string(), |value, filter| {
+                        filter.apply(value)
+                    });
+                output.push_str(&value);
+            }
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let branch = if condition.evaluate(variables) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, variables, output);
+            }
+        }
+    }
+}
+
+/// Returns the English ordinal suffix of a number.
+const fn ordinal_suffix(number: u64) -> &'static str {
+    match (number % 10, number % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders a text with the given variables, panicking if it cannot be parsed.
+    fn render(text: &str, values: &[(&str, &str)]) -> String {
+        let mut variables = Variables::default();
+        for (name, value) in values {
+            variables.insert(name, *value);
+        }
+
+        let names = values.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        TextTemplate::parse(text, &names)
+            .unwrap()
+            .render(&variables)
+    }
+
+    #[test]
+    fn parse_errors_report_positions() {
+        let cases = [
+            ("Hi {name", TextTemplateError::UnclosedPlaceholder(4)),
+            (
+                "Hi {nope}",
+                TextTemplateError::UnknownVariable {
+                    name: "nope".to_string(),
+                    position: 4,
+                },
+            ),
+            (
+                "Hi {name|shout}",
+                TextTemplateError::InvalidFilter {
+                    name: "shout".to_string(),
+                    position: 4,
+                },
+            ),
+            (
+                "{name|truncate:x}",
+                TextTemplateError::InvalidFilter {
+                    name: "truncate:x".to_string(),
+                    position: 1,
+                },
+            ),
+            (
+                "ab{if members >}x{end}",
+                TextTemplateError::InvalidCondition {
+                    condition: "members >".to_string(),
+                    position: 3,
+                },
+            ),
+            (
+                "x{else}",
+                TextTemplateError::UnexpectedTag {
+                    tag: "else".to_string(),
+                    position: 2,
+                },
+            ),
+            (
+                "x{end}",
+                TextTemplateError::UnexpectedTag {
+                    tag: "end".to_string(),
+                    position: 2,
+                },
+            ),
+            (
+                "{if name}a{else}b{else}c{end}",
+                TextTemplateError::UnexpectedTag {
+                    tag: "else".to_string(),
+                    position: 18,
+                },
+            ),
+            ("é {if name}x", TextTemplateError::UnclosedIf(3)),
+        ];
+
+        for (text, error) in cases {
+            assert_eq!(
+                TextTemplate::parse(text, &["name", "members"]),
+                Err(error),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn filters() {
+        let values = [("name", "ferris crab"), ("members", "42")];
+
+        assert_eq!(render("{name|upper}", &values), "FERRIS CRAB");
+        assert_eq!(render("{name|upper|lower}", &values), "ferris crab");
+        assert_eq!(render("{name|capitalize}", &values), "Ferris crab");
+        assert_eq!(render("{name|truncate:6}", &values), "ferri…");
+        assert_eq!(render("{name|truncate:11}", &values), "ferris crab");
+        assert_eq!(render("{name|ordinal}", &values), "ferris crab");
+
+        for (members, ordinal) in [
+            ("1", "1st"),
+            ("2", "2nd"),
+            ("3", "3rd"),
+            ("4", "4th"),
+            ("11", "11th"),
+            ("12", "12th"),
+            ("13", "13th"),
+            ("21", "21st"),
+            ("42", "42nd"),
+            ("113", "113th"),
+        ] {
+            assert_eq!(
+                render("{members|ordinal}", &[("members", members)]),
+                ordinal
+            );
+        }
+    }
+
+    #[test]
+    fn conditions() {
+        let text = "{if members >= 1000}big{else}small{end}";
+        assert_eq!(render(text, &[("members", "1000")]), "big");
+        assert_eq!(render(text, &[("members", "999")]), "small");
+
+        let text = r#"{if name == "Ferris"}crab{end}{if not name}nobody{end}"#;
+        assert_eq!(render(text, &[("name", "Ferris")]), "crab");
+        assert_eq!(render(text, &[("name", "")]), "nobody");
+    }
+
+    #[test]
+    fn nested_conditions() {
+        let text = "{if members > 10}{if name}{name}{else}someone{end} in a crowd{else}alone{end}!";
+
+        assert_eq!(
+            render(text, &[("members", "11"), ("name", "Ferris")]),
+            "Ferris in a crowd!"
+        );
+        assert_eq!(
+            render(text, &[("members", "11"), ("name", "")]),
+            "someone in a crowd!"
+        );
+        assert_eq!(
+            render(text, &[("members", "10"), ("name", "Ferris")]),
+            "alone!"
+        );
+    }
+
+    #[test]
+    fn literal_braces() {
+        assert_eq!(
+            render("{{name}} is {name}", &[("name", "Ferris")]),
+            "{name} is Ferris"
+        );
+    }
+
+    #[test]
+    fn invalid_texts_render_unchanged() {
+        let mut variables = Variables::default();
+        variables.insert("name", "Ferris");
+
+        assert_eq!(variables.render("Hi {nope}"), "Hi {nope}");
+        assert_eq!(variables.render("Hi {name|upper}"), "Hi FERRIS");
+    }
+}
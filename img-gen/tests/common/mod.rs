@@ -0,0 +1,148 @@
+//! Helpers for comparing rendered images against checked-in reference images.
+//!
+//! Reference images live in `tests/golden`. Run the tests with `BLESS_GOLDEN=1` to
+//! write the current output as the new reference after an intended rendering change.
+
+use std::path::PathBuf;
+
+use ab_glyph::FontVec;
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use img_gen::ImageGenerator;
+
+/// The name of the primary test font.
+pub const SANS: &str = "sans";
+/// The name of the monospace test font.
+pub const MONO: &str = "mono";
+
+/// The largest color distance between two pixels which still counts as equal.
+const PIXEL_TOLERANCE: f32 = 12.;
+/// The share of pixels which may differ before a comparison fails.
+const MAX_DIFFERENT_PIXELS: f32 = 0.002;
+
+/// Returns the path of a file in the assets directory of the bot.
+fn asset(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../discord-bot/assets")
+        .join(name)
+}
+
+/// Loads a font from the assets directory of the bot.
+pub fn font(name: &str) -> FontVec {
+    FontVec::try_from_vec(std::fs::read(asset(name)).expect("The font could not be read."))
+        .expect("The font is invalid.")
+}
+
+/// Creates a generator with the fonts used by the bot.
+pub fn generator() -> ImageGenerator {
+    let mut generator = ImageGenerator::with_cache_capacity(0);
+    generator.add_font(SANS, font("FiraSans-Bold.ttf"));
+    generator.add_font(MONO, font("FiraMono-Medium.ttf"));
+
+    generator
+}
+
+/// Creates an image filled with a single color.
+pub fn solid(width: u32, height: u32, color: [u8; 4]) -> DynamicImage {
+    DynamicImage::ImageRgba8(RgbaImage::from_pixel(width, height, Rgba(color)))
+}
+
+/// Creates an image with a diagonal color gradient, which makes misplaced overlays visible.
+pub fn gradient(width: u32, height: u32) -> DynamicImage {
+    DynamicImage::ImageRgba8(RgbaImage::from_fn(width, height, |x, y| {
+        Rgba([
+            (x * 255 / width.max(1)) as u8,
+            (y * 255 / height.max(1)) as u8,
+            160,
+            255,
+        ])
+    }))
+}
+
+/// Returns the perceptual distance between two colors.
+///
+/// The channels are weighted by how sensitive the eye is to them, and the colors
+/// are blended onto black so differences in invisible pixels are ignored.
+fn distance(a: Rgba<u8>, b: Rgba<u8>) -> f32 {
+    let premultiply =
+        |pixel: Rgba<u8>, channel: usize| f32::from(pixel[channel]) * f32::from(pixel[3]) / 255.;
+
+    let dr = premultiply(a, 0) - premultiply(b, 0);
+    let dg = premultiply(a, 1) - premultiply(b, 1);
+    let db = premultiply(a, 2) - premultiply(b, 2);
+    let da = f32::from(a[3]) - f32::from(b[3]);
+
+    (0.3 * dr * dr + 0.59 * dg * dg + 0.11 * db * db + da * da).sqrt()
+}
+
+/// Compares a rendered image against the reference image with the given name.
+///
+/// On failure the rendered image and a diff image, which marks differing pixels in red
+/// on top of the faded reference, are written to the temporary directory of the tests.
+///
+/// # Panics
+/// Panics if the images differ by more than the tolerance or the reference is missing.
+pub fn assert_golden(name: &str, actual: &DynamicImage) {
+    let reference_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"));
+
+    if std::env::var_os("BLESS_GOLDEN").is_some() {
+        actual
+            .save(&reference_path)
+            .expect("The reference image could not be written.");
+        return;
+    }
+
+    let Ok(expected) = image::open(&reference_path) else {
+        panic!(
+            "The reference image {} is missing. Run the tests with BLESS_GOLDEN=1 to create it.",
+            reference_path.display()
+        );
+    };
+
+    let output_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    std::fs::create_dir_all(&output_dir).expect("The output directory could not be created.");
+    let actual_path = output_dir.join(format!("{name}.actual.png"));
+
+    if expected.dimensions() != actual.dimensions() {
+        actual.save(&actual_path).ok();
+        panic!(
+            "{name}: expected a {:?} image, but rendered {:?}. The output was written to {}.",
+            expected.dimensions(),
+            actual.dimensions(),
+            actual_path.display()
+        );
+    }
+
+    let mut different_pixels = 0;
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+
+    for (x, y, expected_pixel) in expected.pixels() {
+        let actual_pixel = actual.get_pixel(x, y);
+
+        if distance(expected_pixel, actual_pixel) > PIXEL_TOLERANCE {
+            different_pixels += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        } else {
+            let [r, g, b, _] = expected_pixel.0;
+            let gray = ((u16::from(r) + u16::from(g) + u16::from(b)) / 6) as u8;
+            diff.put_pixel(x, y, Rgba([gray, gray, gray, 255]));
+        }
+    }
+
+    let share = different_pixels as f32 / (actual.width() * actual.height()) as f32;
+
+    if share > MAX_DIFFERENT_PIXELS {
+        let diff_path = output_dir.join(format!("{name}.diff.png"));
+        actual.save(&actual_path).ok();
+        diff.save(&diff_path).ok();
+
+        panic!(
+            "{name}: {different_pixels} pixels ({:.2}%) differ from the reference. \
+             The output was written to {} and the differences to {}.",
+            share * 100.,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
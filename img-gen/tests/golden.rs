@@ -0,0 +1,198 @@
+//! Renders fixed image builders and compares them against the reference images in
+//! `tests/golden`.
+
+mod common;
+
+use ab_glyph::PxScale;
+use common::{assert_golden, generator, gradient, solid, MONO, SANS};
+use image::Rgba;
+use img_gen::{
+    color::Color,
+    effects::{Stroke, TextEffects},
+    error::Error,
+    mask::Mask,
+    picture::{BlendMode, PictureSize, PictureStyle, ResizeMode},
+    shape::{ColorStop, Fill, Shape},
+    text::{HorizontalAlign, TextBox, VerticalAlign},
+    ImageBuilder,
+};
+
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const BACKGROUND: [u8; 4] = [32, 34, 37, 255];
+
+#[test]
+fn centered_text() {
+    let builder = ImageBuilder::new(solid(320, 120, BACKGROUND))
+        .add_text("Welcome!", 160, 40, PxScale::from(36.), SANS, WHITE, true)
+        .add_text("member #42", 160, 85, PxScale::from(20.), MONO, WHITE, true);
+
+    let image = generator().generate(builder).unwrap();
+    assert_golden("centered_text", &image);
+}
+
+#[test]
+fn text_box_wraps_and_aligns() {
+    let bounds = |x, align| TextBox {
+        x,
+        y: 10,
+        width: 140,
+        height: 100,
+        max_scale: 28.,
+        min_scale: 12.,
+        align,
+        vertical_align: VerticalAlign::Middle,
+    };
+
+    let builder = ImageBuilder::new(solid(320, 120, BACKGROUND))
+        .add_text_box(
+            "A rather long display name",
+            bounds(10, HorizontalAlign::Left),
+            SANS,
+            WHITE,
+        )
+        .add_text_box(
+            "Right aligned text",
+            bounds(170, HorizontalAlign::Right),
+            SANS,
+            WHITE,
+        );
+
+    let image = generator().generate(builder).unwrap();
+    assert_golden("text_box", &image);
+}
+
+#[test]
+fn text_with_stroke() {
+    let builder = ImageBuilder::new(solid(320, 80, [90, 120, 200, 255]))
+        .add_text("Outlined", 160, 40, PxScale::from(40.), SANS, WHITE, true)
+        .with_text_effects(TextEffects {
+            stroke: Some(Stroke {
+                width: 3,
+                color: Color(Rgba([0, 0, 0, 255])),
+            }),
+            ..TextEffects::default()
+        });
+
+    let image = generator().generate(builder).unwrap();
+    assert_golden("text_stroke", &image);
+}
+
+#[test]
+fn masked_and_styled_overlays() {
+    let builder = ImageBuilder::new(solid(320, 120, BACKGROUND))
+        .add_masked_image(gradient(100, 100), 10, 10, Mask::Circle)
+        .add_masked_image(
+            gradient(64, 64),
+            120,
+            10,
+            Mask::RoundedRectangle { radius: 16 },
+        )
+        .with_picture_style(PictureStyle {
+            size: Some(PictureSize {
+                width: 100,
+                height: 100,
+                mode: ResizeMode::Fill,
+            }),
+            ..PictureStyle::default()
+        })
+        .add_image(gradient(60, 60), 240, 30)
+        .with_picture_style(PictureStyle {
+            rotation: 30.,
+            opacity: 0.6,
+            blend: BlendMode::Screen,
+            ..PictureStyle::default()
+        });
+
+    let image = generator().generate(builder).unwrap();
+    assert_golden("overlays", &image);
+}
+
+#[test]
+fn shapes_and_gradients() {
+    let stops = vec![
+        ColorStop {
+            offset: 0.,
+            color: Color(Rgba([255, 80, 80, 255])),
+        },
+        ColorStop {
+            offset: 1.,
+            color: Color(Rgba([80, 80, 255, 0])),
+        },
+    ];
+
+    let builder = ImageBuilder::new(solid(320, 120, BACKGROUND))
+        .add_shape(
+            Shape::RoundedRectangle {
+                x: 10,
+                y: 10,
+                width: 140,
+                height: 100,
+                radius: 20,
+            },
+            Fill::LinearGradient {
+                angle: 45.,
+                stops: stops.clone(),
+            },
+            1.,
+        )
+        .add_shape(
+            Shape::Circle {
+                x: 230,
+                y: 60,
+                radius: 50,
+            },
+            Fill::RadialGradient { stops },
+            1.,
+        )
+        .add_shape(
+            Shape::Line {
+                x1: 10,
+                y1: 115,
+                x2: 310,
+                y2: 100,
+                width: 3,
+            },
+            Fill::Solid {
+                color: Color(WHITE),
+            },
+            0.8,
+        );
+
+    let image = generator().generate(builder).unwrap();
+    assert_golden("shapes", &image);
+}
+
+#[test]
+fn fallback_font_covers_missing_glyphs() {
+    let mut generator = generator();
+    generator.add_fallback_font(common::font("DejaVuSans.ttf"));
+
+    let builder = ImageBuilder::new(solid(320, 80, BACKGROUND)).add_text(
+        "Բարեւ, გამარჯობა!",
+        160,
+        40,
+        PxScale::from(32.),
+        SANS,
+        WHITE,
+        true,
+    );
+
+    let image = generator.generate(builder).unwrap();
+    assert_golden("fallback_font", &image);
+}
+
+#[test]
+fn missing_font_is_an_error() {
+    let builder = ImageBuilder::new(solid(64, 64, BACKGROUND)).add_text(
+        "Hello",
+        0,
+        0,
+        PxScale::from(16.),
+        "does-not-exist",
+        WHITE,
+        false,
+    );
+
+    let result = generator().generate(builder);
+    assert!(matches!(result, Err(Error::FontNotFound(_))));
+}
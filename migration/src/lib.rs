@@ -12,6 +12,12 @@ mod m20250327_133628_twitch_tables;
 mod m20250404_204558_broadcaster_based_tokens;
 mod m20250525_091835_webuser_tables;
 mod m20250618_085627_update_kick_ban_log;
+mod m20261017_090000_welcome_image_template;
+mod m20261017_100000_goodbye_settings;
+mod m20261017_110000_welcome_dm;
+mod m20261017_120000_join_roles;
+mod m20261017_130000_welcome_variants;
+mod m20261017_140000_join_bursts;
 
 
 pub struct Migrator;
@@ -32,6 +38,12 @@ impl MigratorTrait for Migrator {
             Box::new(m20250404_204558_broadcaster_based_tokens::Migration),
             Box::new(m20250525_091835_webuser_tables::Migration),
             Box::new(m20250618_085627_update_kick_ban_log::Migration),
+            Box::new(m20261017_090000_welcome_image_template::Migration),
+            Box::new(m20261017_100000_goodbye_settings::Migration),
+            Box::new(m20261017_110000_welcome_dm::Migration),
+            Box::new(m20261017_120000_join_roles::Migration),
+            Box::new(m20261017_130000_welcome_variants::Migration),
+            Box::new(m20261017_140000_join_bursts::Migration),
         ]
     }
 }
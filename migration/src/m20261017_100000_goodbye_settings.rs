@@ -0,0 +1,97 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GoodbyeSettings::Table)
+                    .if_not_exists()
+                    .col(pk_auto(GoodbyeSettings::Id))
+                    .col(big_integer(GoodbyeSettings::GoodbyeChannel))
+                    .col(text(GoodbyeSettings::ChatMessage))
+                    .col(text(GoodbyeSettings::ImageHeadline))
+                    .col(text(GoodbyeSettings::ImageSubtext))
+                    .col(integer(GoodbyeSettings::BackBanner))
+                    .col(integer(GoodbyeSettings::FrontBanner))
+                    .col(boolean(GoodbyeSettings::Enabled).default(false))
+                    .col(boolean(GoodbyeSettings::CardEnabled).default(false))
+                    .col(text_null(GoodbyeSettings::ImageTemplate))
+                    .col(big_integer(GoodbyeSettings::CreateUserId))
+                    .col(text(GoodbyeSettings::CreateDate))
+                    .col(big_integer_null(GoodbyeSettings::ModifyUserId))
+                    .col(text_null(GoodbyeSettings::ModifyDate))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GoodbyeSettings::Table, GoodbyeSettings::BackBanner)
+                            .to(Image::Table, Image::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GoodbyeSettings::Table, GoodbyeSettings::FrontBanner)
+                            .to(Image::Table, Image::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Guild::Table)
+                    .add_column_if_not_exists(integer_null(Guild::GoodbyeSettingsId))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Guild::Table)
+                    .drop_column(Guild::GoodbyeSettingsId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(GoodbyeSettings::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GoodbyeSettings {
+    Table,
+    Id,
+    GoodbyeChannel,
+    ChatMessage,
+    ImageHeadline,
+    ImageSubtext,
+    BackBanner,
+    FrontBanner,
+    Enabled,
+    CardEnabled,
+    ImageTemplate,
+    CreateUserId,
+    CreateDate,
+    ModifyUserId,
+    ModifyDate,
+}
+
+#[derive(DeriveIden)]
+enum Guild {
+    Table,
+    GoodbyeSettingsId,
+}
+
+#[derive(DeriveIden)]
+enum Image {
+    Table,
+    Id,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WelcomeSettings::Table)
+                    .add_column_if_not_exists(boolean(WelcomeSettings::DmEnabled).default(false))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WelcomeSettings::Table)
+                    .add_column_if_not_exists(
+                        text(WelcomeSettings::DmMessage)
+                            .default("Welcome to **{guild}**, {name}! Please read the rules before you start chatting."),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WelcomeSettings::Table)
+                    .add_column_if_not_exists(boolean(WelcomeSettings::DmCard).default(false))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            WelcomeSettings::DmEnabled,
+            WelcomeSettings::DmMessage,
+            WelcomeSettings::DmCard,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(WelcomeSettings::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum WelcomeSettings {
+    Table,
+    DmEnabled,
+    DmMessage,
+    DmCard,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JoinRole::Table)
+                    .if_not_exists()
+                    .col(pk_auto(JoinRole::Id))
+                    .col(integer(JoinRole::GuildId))
+                    .col(big_integer(JoinRole::RoleId))
+                    .col(boolean(JoinRole::ForBots).default(false))
+                    .col(integer(JoinRole::DelaySeconds).default(0))
+                    .col(big_integer(JoinRole::CreateUserId))
+                    .col(date_time(JoinRole::CreateDate))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(JoinRole::Table, JoinRole::GuildId)
+                            .to(Guild::Table, Guild::Id),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(JoinRole::GuildId)
+                            .col(JoinRole::RoleId),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JoinRole::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JoinRole {
+    Table,
+    Id,
+    GuildId,
+    RoleId,
+    ForBots,
+    DelaySeconds,
+    CreateUserId,
+    CreateDate,
+}
+
+#[derive(DeriveIden)]
+enum Guild {
+    Table,
+    Id,
+}
@@ -0,0 +1,127 @@
+use sea_orm_migration::{
+    prelude::*,
+    schema::*,
+    sea_orm::{EnumIter, Iterable},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WelcomeVariant::Table)
+                    .if_not_exists()
+                    .col(pk_auto(WelcomeVariant::Id))
+                    .col(integer(WelcomeVariant::WelcomeSettingsId))
+                    .col(string_len(WelcomeVariant::Name, 50))
+                    .col(text_null(WelcomeVariant::ChatMessage))
+                    .col(text_null(WelcomeVariant::ImageHeadline))
+                    .col(text_null(WelcomeVariant::ImageSubtext))
+                    .col(integer_null(WelcomeVariant::BackBanner))
+                    .col(integer_null(WelcomeVariant::FrontBanner))
+                    .col(integer(WelcomeVariant::Weight).default(1))
+                    .col(string_len_null(WelcomeVariant::StartDate, 5))
+                    .col(string_len_null(WelcomeVariant::EndDate, 5))
+                    .col(big_integer(WelcomeVariant::CreateUserId))
+                    .col(date_time(WelcomeVariant::CreateDate))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(WelcomeVariant::Table, WelcomeVariant::WelcomeSettingsId)
+                            .to(WelcomeSettings::Table, WelcomeSettings::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(WelcomeVariant::Table, WelcomeVariant::BackBanner)
+                            .to(Image::Table, Image::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(WelcomeVariant::Table, WelcomeVariant::FrontBanner)
+                            .to(Image::Table, Image::Id),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(WelcomeVariant::WelcomeSettingsId)
+                            .col(WelcomeVariant::Name),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WelcomeSettings::Table)
+                    .add_column_if_not_exists(
+                        enumeration(
+                            WelcomeSettings::VariantMode,
+                            Alias::new("variant_mode"),
+                            VariantMode::iter(),
+                        )
+                        .default("random"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WelcomeSettings::Table)
+                    .drop_column(WelcomeSettings::VariantMode)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(WelcomeVariant::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WelcomeVariant {
+    Table,
+    Id,
+    WelcomeSettingsId,
+    Name,
+    ChatMessage,
+    ImageHeadline,
+    ImageSubtext,
+    BackBanner,
+    FrontBanner,
+    Weight,
+    StartDate,
+    EndDate,
+    CreateUserId,
+    CreateDate,
+}
+
+#[derive(DeriveIden)]
+enum WelcomeSettings {
+    Table,
+    Id,
+    VariantMode,
+}
+
+#[derive(DeriveIden)]
+enum Image {
+    Table,
+    Id,
+}
+
+#[derive(Iden, EnumIter, Default)]
+pub enum VariantMode {
+    #[default]
+    #[iden = "random"]
+    Random,
+    #[iden = "round_robin"]
+    RoundRobin,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WelcomeSettings::Table)
+                    .add_column_if_not_exists(integer(WelcomeSettings::BurstThreshold).default(10))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WelcomeSettings::Table)
+                    .add_column_if_not_exists(integer(WelcomeSettings::BurstInterval).default(30))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WelcomeSettings::Table)
+                    .add_column_if_not_exists(
+                        text(WelcomeSettings::BurstMessage)
+                            .default("Welcome {mentions} to **{guild}**!"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            WelcomeSettings::BurstThreshold,
+            WelcomeSettings::BurstInterval,
+            WelcomeSettings::BurstMessage,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(WelcomeSettings::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum WelcomeSettings {
+    Table,
+    BurstThreshold,
+    BurstInterval,
+    BurstMessage,
+}
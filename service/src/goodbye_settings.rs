@@ -0,0 +1,88 @@
+use ::entity::goodbye_settings::{self, Entity as GoodbyeSettings};
+
+use sea_orm::{ActiveModelTrait, DbConn, DbErr, EntityTrait, Set};
+
+/// Creates new goodbye settings.
+///
+/// # Errors
+///
+/// Will return `Err` if database operation fail. For more information look at [DbErr](https://docs.rs/sea-orm/latest/sea_orm/error/enum.DbErr.html).
+#[fastrace::trace]
+pub async fn create(
+    db: &DbConn,
+    goodbye_settings: goodbye_settings::Model,
+) -> Result<goodbye_settings::Model, DbErr> {
+    goodbye_settings::ActiveModel {
+        goodbye_channel: Set(goodbye_settings.goodbye_channel),
+        chat_message: Set(goodbye_settings.chat_message),
+        image_headline: Set(goodbye_settings.image_headline),
+        image_subtext: Set(goodbye_settings.image_subtext),
+        front_banner: Set(goodbye_settings.front_banner),
+        back_banner: Set(goodbye_settings.back_banner),
+        enabled: Set(goodbye_settings.enabled),
+        card_enabled: Set(goodbye_settings.card_enabled),
+        image_template: Set(goodbye_settings.image_template),
+        create_user_id: Set(goodbye_settings.create_user_id),
+        create_date: Set(goodbye_settings.create_date),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Updates the details of goodbye settings.
+///
+/// # Errors
+///
+/// Will return `Err` if database operation fail. For more information look at [DbErr](https://docs.rs/sea-orm/latest/sea_orm/error/enum.DbErr.html).
+#[fastrace::trace]
+pub async fn update(
+    db: &DbConn,
+    update_goodbye_settings: goodbye_settings::Model,
+) -> Result<Option<goodbye_settings::Model>, DbErr> {
+    let goodbye_settings: goodbye_settings::ActiveModel =
+        match GoodbyeSettings::find_by_id(update_goodbye_settings.id)
+            .one(db)
+            .await?
+        {
+            Some(m) => m.into(),
+            None => return Ok(None),
+        };
+
+    let updated = goodbye_settings::ActiveModel {
+        id: goodbye_settings.id,
+        goodbye_channel: Set(update_goodbye_settings.goodbye_channel),
+        chat_message: Set(update_goodbye_settings.chat_message),
+        image_headline: Set(update_goodbye_settings.image_headline),
+        image_subtext: Set(update_goodbye_settings.image_subtext),
+        front_banner: Set(update_goodbye_settings.front_banner),
+        back_banner: Set(update_goodbye_settings.back_banner),
+        enabled: Set(update_goodbye_settings.enabled),
+        card_enabled: Set(update_goodbye_settings.card_enabled),
+        image_template: Set(update_goodbye_settings.image_template),
+        create_date: goodbye_settings.create_date,
+        create_user_id: goodbye_settings.create_user_id,
+        modify_date: Set(update_goodbye_settings.modify_date),
+        modify_user_id: Set(update_goodbye_settings.modify_user_id),
+    }
+    .update(db)
+    .await?;
+
+    Ok(Some(updated))
+}
+
+/// Retrieves goodbye settings by their ID.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `id` - The ID of the goodbye settings to retrieve.
+///
+/// # Returns
+/// Returns an [`Option`] containing the goodbye settings model if found, or `None` if no settings are found.
+///
+/// # Errors
+/// Returns a [`DbErr`] if the database operation fails.
+#[fastrace::trace]
+pub async fn get_one(db: &DbConn, id: i32) -> Result<Option<goodbye_settings::Model>, DbErr> {
+    GoodbyeSettings::find_by_id(id).one(db).await
+}
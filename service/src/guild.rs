@@ -15,6 +15,7 @@ pub async fn create(db: &DbConn, guild: guild::Model) -> Result<guild::Model, Db
         guild_id: Set(guild.guild_id),
         moderation_channel_id: Set(guild.moderation_channel_id),
         welcome_settings_id: Set(guild.welcome_settings_id),
+        goodbye_settings_id: Set(guild.goodbye_settings_id),
         auto_ban_role_id: Set(guild.auto_ban_role_id),
         ban_reason_template: Set(guild.ban_reason_template),
         create_user_id: Set(guild.create_user_id),
@@ -40,6 +41,7 @@ pub async fn get_or_create<T: AsRef<str> + std::marker::Send>(
             name: guild_name.as_ref().to_string(),
             guild_id,
             welcome_settings_id: None,
+            goodbye_settings_id: None,
             moderation_channel_id: None,
             auto_ban_role_id: None,
             ban_reason_template: None,
@@ -75,6 +77,7 @@ pub async fn update(
         guild_id: Set(update_guild.guild_id),
         moderation_channel_id: Set(update_guild.moderation_channel_id),
         welcome_settings_id: Set(update_guild.welcome_settings_id),
+        goodbye_settings_id: Set(update_guild.goodbye_settings_id),
         auto_ban_role_id: Set(update_guild.auto_ban_role_id),
         punish_mode: Set(update_guild.punish_mode.clone()),
         ban_reason_template: Set(update_guild.ban_reason_template.clone()),
@@ -0,0 +1,73 @@
+use ::entity::join_role::{self, Entity as JoinRole};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DbConn, DbErr, DeleteResult, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+
+/// Creates a new join role in the database.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `new_model` - The join role model to insert.
+///
+/// # Errors
+/// Returns a [`DbErr`] if the database operation fails.
+#[fastrace::trace]
+pub async fn create(db: &DbConn, new_model: join_role::Model) -> Result<join_role::Model, DbErr> {
+    join_role::ActiveModel {
+        guild_id: Set(new_model.guild_id),
+        role_id: Set(new_model.role_id),
+        for_bots: Set(new_model.for_bots),
+        delay_seconds: Set(new_model.delay_seconds),
+        create_user_id: Set(new_model.create_user_id),
+        create_date: Set(new_model.create_date),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Deletes a join role by role ID in the database.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `guild_id` - The guild ID associated with the join role.
+/// * `role_id` - The Discord role ID of the join role to delete.
+///
+/// # Errors
+/// Returns a [`DbErr`] if the database operation fails.
+#[fastrace::trace]
+pub async fn delete_by_role_id(
+    db: &DbConn,
+    guild_id: i32,
+    role_id: i64,
+) -> Result<DeleteResult, DbErr> {
+    JoinRole::delete_many()
+        .filter(
+            join_role::Column::GuildId
+                .eq(guild_id)
+                .and(join_role::Column::RoleId.eq(role_id)),
+        )
+        .exec(db)
+        .await
+}
+
+/// Retrieves all join roles for a specific guild.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `guild_id` - The ID of the guild to retrieve join roles for.
+///
+/// # Returns
+/// Returns a `Vec` containing all join role models for the specified guild, ordered by delay.
+///
+/// # Errors
+/// Returns a [`DbErr`] if the database operation fails.
+#[fastrace::trace]
+pub async fn get_all(db: &DbConn, guild_id: i32) -> Result<Vec<join_role::Model>, DbErr> {
+    JoinRole::find()
+        .filter(join_role::Column::GuildId.eq(guild_id))
+        .order_by_asc(join_role::Column::DelaySeconds)
+        .all(db)
+        .await
+}
@@ -1,8 +1,11 @@
 pub mod ban_entry;
+pub mod goodbye_settings;
 pub mod guild;
 pub mod image;
+pub mod join_role;
 pub mod twitch_broadcaster;
 pub mod welcome_settings;
+pub mod welcome_variant;
 pub mod web_user;
 pub mod kick_entry;
 
@@ -19,6 +19,14 @@ pub async fn create(
         image_subtext: Set(welcome_settings.image_subtext),
         front_banner: Set(welcome_settings.front_banner),
         back_banner: Set(welcome_settings.back_banner),
+        image_template: Set(welcome_settings.image_template),
+        dm_enabled: Set(welcome_settings.dm_enabled),
+        dm_message: Set(welcome_settings.dm_message),
+        dm_card: Set(welcome_settings.dm_card),
+        variant_mode: Set(welcome_settings.variant_mode),
+        burst_threshold: Set(welcome_settings.burst_threshold),
+        burst_interval: Set(welcome_settings.burst_interval),
+        burst_message: Set(welcome_settings.burst_message),
         create_user_id: Set(welcome_settings.create_user_id),
         create_date: Set(welcome_settings.create_date),
         ..Default::default()
@@ -55,6 +63,14 @@ pub async fn update(
         front_banner: Set(update_welcome_settings.front_banner),
         back_banner: Set(update_welcome_settings.back_banner),
         enabled: Set(update_welcome_settings.enabled),
+        image_template: Set(update_welcome_settings.image_template),
+        dm_enabled: Set(update_welcome_settings.dm_enabled),
+        dm_message: Set(update_welcome_settings.dm_message),
+        dm_card: Set(update_welcome_settings.dm_card),
+        variant_mode: Set(update_welcome_settings.variant_mode),
+        burst_threshold: Set(update_welcome_settings.burst_threshold),
+        burst_interval: Set(update_welcome_settings.burst_interval),
+        burst_message: Set(update_welcome_settings.burst_message),
         create_date: welcome_settings.create_date,
         create_user_id: welcome_settings.create_user_id,
         modify_date: Set(update_welcome_settings.modify_date),
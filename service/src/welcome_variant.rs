@@ -0,0 +1,85 @@
+use ::entity::welcome_variant::{self, Entity as WelcomeVariant};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DbConn, DbErr, DeleteResult, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+
+/// Creates a new welcome variant in the database.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `new_model` - The welcome variant model to insert.
+///
+/// # Errors
+/// Returns a [`DbErr`] if the database operation fails.
+#[fastrace::trace]
+pub async fn create(
+    db: &DbConn,
+    new_model: welcome_variant::Model,
+) -> Result<welcome_variant::Model, DbErr> {
+    welcome_variant::ActiveModel {
+        welcome_settings_id: Set(new_model.welcome_settings_id),
+        name: Set(new_model.name),
+        chat_message: Set(new_model.chat_message),
+        image_headline: Set(new_model.image_headline),
+        image_subtext: Set(new_model.image_subtext),
+        back_banner: Set(new_model.back_banner),
+        front_banner: Set(new_model.front_banner),
+        weight: Set(new_model.weight),
+        start_date: Set(new_model.start_date),
+        end_date: Set(new_model.end_date),
+        create_user_id: Set(new_model.create_user_id),
+        create_date: Set(new_model.create_date),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Deletes a welcome variant by name in the database.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `welcome_settings_id` - The ID of the welcome settings the variant belongs to.
+/// * `name` - The name of the variant to delete.
+///
+/// # Errors
+/// Returns a [`DbErr`] if the database operation fails.
+#[fastrace::trace]
+pub async fn delete_by_name(
+    db: &DbConn,
+    welcome_settings_id: i32,
+    name: &str,
+) -> Result<DeleteResult, DbErr> {
+    WelcomeVariant::delete_many()
+        .filter(
+            welcome_variant::Column::WelcomeSettingsId
+                .eq(welcome_settings_id)
+                .and(welcome_variant::Column::Name.eq(name)),
+        )
+        .exec(db)
+        .await
+}
+
+/// Retrieves all welcome variants of welcome settings.
+///
+/// # Arguments
+/// * `db` - The database connection.
+/// * `welcome_settings_id` - The ID of the welcome settings to retrieve variants for.
+///
+/// # Returns
+/// Returns a `Vec` containing all welcome variant models, ordered by name.
+///
+/// # Errors
+/// Returns a [`DbErr`] if the database operation fails.
+#[fastrace::trace]
+pub async fn get_all(
+    db: &DbConn,
+    welcome_settings_id: i32,
+) -> Result<Vec<welcome_variant::Model>, DbErr> {
+    WelcomeVariant::find()
+        .filter(welcome_variant::Column::WelcomeSettingsId.eq(welcome_settings_id))
+        .order_by_asc(welcome_variant::Column::Name)
+        .all(db)
+        .await
+}